@@ -1,22 +1,105 @@
 use syn::punctuated::Punctuated;
-use syn::{LitStr, Token};
+use syn::{LitInt, LitStr, Token};
 
-use crate::term::Term;
+use crate::constraint::Constraint;
+use crate::doc::doc_help;
+use crate::term::{Parser, Term};
+use crate::ty::wrapper;
 
 pub struct ArgumentMeta {
     pub help: Option<LitStr>,
+    pub long_help: Option<String>,
+    /// Converts values with `FromStr` or a function instead of `Validator`.
+    pub parser: Option<Parser>,
+    /// Checks of each value after it is converted.
+    pub constraints: Vec<Constraint>,
+    pub trailing: bool,
+    /// Whether the argument is an `Option`, which may be omitted.
+    pub optional: bool,
+    /// Whether the argument is a `Vec`, which takes from `min` to `max` values.
+    pub variadic: bool,
+    pub min: Option<LitInt>,
+    pub max: Option<LitInt>,
 }
 
 impl ArgumentMeta {
     pub fn new() -> Self {
-        Self { help: None }
+        Self {
+            help: None,
+            long_help: None,
+            parser: None,
+            constraints: Vec::new(),
+            trailing: false,
+            optional: false,
+            variadic: false,
+            min: None,
+            max: None,
+        }
     }
 
     pub fn from(attr: &syn::Attribute) -> Self {
         if attr.tokens.is_empty() {
             Self::new()
         } else {
-            attr.parse_args().unwrap()
+            attr.parse_args().unwrap_or_else(|err| panic!("{}", err))
+        }
+    }
+
+    /// Uses the doc comments of the field when `help` is not given.
+    pub fn with_doc(mut self, attrs: &[syn::Attribute]) -> Self {
+        if self.help.is_none() {
+            if let Some((help, long_help)) = doc_help(attrs) {
+                self.help = Some(help);
+                self.long_help = Some(long_help);
+            }
+        }
+        self
+    }
+
+    /// Reads whether the argument may be omitted or repeated from its type.
+    pub fn with_type(mut self, ty: &syn::Type) -> Self {
+        match wrapper(ty) {
+            Some("Option") => self.optional = true,
+            Some("Vec") if !self.trailing => self.variadic = true,
+            _ => {}
+        }
+        if self.trailing && wrapper(ty) != Some("Vec") {
+            panic!("trailing is only supported for a Vec argument, which collects every remaining token");
+        }
+        if !self.variadic && (self.min.is_some() || self.max.is_some()) {
+            panic!("min and max are only supported for a Vec argument");
+        }
+        if matches!(self.max(), Some(max) if max < self.min()) {
+            panic!("max must not be less than min");
+        }
+        self
+    }
+
+    /// The least number of values, which is 1 for a variadic argument unless `min` is given.
+    pub fn min(&self) -> usize {
+        match &self.min {
+            Some(min) => min.base10_parse().unwrap(),
+            None if self.optional || self.trailing => 0,
+            None => 1,
+        }
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.max.as_ref().map(|max| max.base10_parse().unwrap())
+    }
+
+    /// How the argument is shown in the usage, e.g. `<path>`, `[path]`, `<files>...` or `[ARGS]...`.
+    pub fn usage(&self, name: &str) -> String {
+        if self.trailing {
+            format!("[{}]...", name.to_uppercase())
+        } else if self.variadic && self.min() == 0 {
+            format!("[{}]...", name)
+        } else if self.variadic {
+            format!("<{}>...", name)
+        } else if self.optional {
+            format!("[{}]", name)
+        } else {
+            format!("<{}>", name)
         }
     }
 }
@@ -30,6 +113,24 @@ impl syn::parse::Parse for ArgumentMeta {
                 Term::Help(lit) => {
                     meta.help = Some(lit);
                 }
+                Term::ParseWith => {
+                    meta.parser = Some(Parser::FromStr);
+                }
+                Term::Parser(path) => {
+                    meta.parser = Some(Parser::Path(path));
+                }
+                Term::Constraint(constraint) => {
+                    meta.constraints.push(constraint);
+                }
+                Term::Trailing => {
+                    meta.trailing = true;
+                }
+                Term::Min(lit) => {
+                    meta.min = Some(lit);
+                }
+                Term::Max(lit) => {
+                    meta.max = Some(lit);
+                }
                 _ => return Err(input.error("un support type")),
             }
         }
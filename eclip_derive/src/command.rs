@@ -0,0 +1,63 @@
+use syn::punctuated::Punctuated;
+use syn::Token;
+
+use crate::doc::{doc_comment, first_paragraph};
+use crate::term::Term;
+
+pub struct CommandMeta {
+    pub about: Option<String>,
+    pub long_about: Option<String>,
+}
+
+impl CommandMeta {
+    pub fn new() -> Self {
+        Self {
+            about: None,
+            long_about: None,
+        }
+    }
+
+    /// Reads `#[command(...)]`, falling back to the doc comments for the texts not given.
+    pub fn from(attrs: &[syn::Attribute]) -> Self {
+        let mut meta = Self::new();
+        for attr in attrs {
+            if attr.path.is_ident("command") && !attr.tokens.is_empty() {
+                let command: CommandMeta = attr.parse_args().unwrap();
+                meta.about = command.about.or(meta.about);
+                meta.long_about = command.long_about.or(meta.long_about);
+            }
+        }
+        if let Some(doc) = doc_comment(attrs) {
+            if meta.about.is_none() {
+                meta.about = Some(first_paragraph(&doc));
+            }
+            if meta.long_about.is_none() {
+                meta.long_about = Some(doc);
+            }
+        }
+        meta
+    }
+
+    pub fn long_about(&self) -> Option<&String> {
+        self.long_about.as_ref().or(self.about.as_ref())
+    }
+}
+
+impl syn::parse::Parse for CommandMeta {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let terms: Punctuated<Term, Token![,]> = Punctuated::parse_terminated(input)?;
+        let mut meta = CommandMeta::new();
+        for term in terms.into_iter() {
+            match term {
+                Term::About(lit) => {
+                    meta.about = Some(lit.value());
+                }
+                Term::LongAbout(lit) => {
+                    meta.long_about = Some(lit.value());
+                }
+                _ => return Err(input.error("un support type")),
+            }
+        }
+        Ok(meta)
+    }
+}
@@ -0,0 +1,95 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{LitInt, LitStr};
+
+/// A check of a converted value, e.g. `range = 1..=65535`.
+pub enum Constraint {
+    Range(syn::ExprRange),
+    MinLen(LitInt),
+    MaxLen(LitInt),
+    Regex(LitStr),
+    PossibleValues(Vec<LitStr>),
+    ValidateWith(syn::Path),
+}
+
+fn compact(tokens: TokenStream) -> String {
+    tokens.to_string().replace(' ', "")
+}
+
+/// Shows a range as in help, e.g. `1-65535` for `1..=65535`.
+fn range_text(range: &syn::ExprRange) -> String {
+    let from = range.from.as_ref().map(|from| compact(quote!(#from)));
+    let to = range.to.as_ref().map(|to| compact(quote!(#to)));
+    match (from, &range.limits, to) {
+        (Some(from), syn::RangeLimits::Closed(_), Some(to)) => format!("{}-{}", from, to),
+        (from, syn::RangeLimits::Closed(_), to) => {
+            format!("{}..={}", from.unwrap_or_default(), to.unwrap_or_default())
+        }
+        (from, syn::RangeLimits::HalfOpen(_), to) => {
+            format!("{}..{}", from.unwrap_or_default(), to.unwrap_or_default())
+        }
+    }
+}
+
+/// Describes the accepted values for the placeholder of an option, e.g. `1-65535` or `a|b`.
+pub fn describe(constraints: &[Constraint]) -> Option<String> {
+    constraints.iter().find_map(|constraint| match constraint {
+        Constraint::Range(range) => Some(range_text(range)),
+        Constraint::PossibleValues(values) => Some(
+            values
+                .iter()
+                .map(|value| value.value())
+                .collect::<Vec<_>>()
+                .join("|"),
+        ),
+        _ => None,
+    })
+}
+
+/// The values of `possible_values`, which are offered by shell completions.
+pub fn choices(constraints: &[Constraint]) -> Option<&[LitStr]> {
+    constraints.iter().find_map(|constraint| match constraint {
+        Constraint::PossibleValues(values) => Some(values.as_slice()),
+        _ => None,
+    })
+}
+
+/// Checks `value`, a reference to one converted value, returning early on a failure.
+pub fn checks(constraints: &[Constraint]) -> Vec<TokenStream> {
+    let mut checks = Vec::new();
+    let mut min_len = quote!(None);
+    let mut max_len = quote!(None);
+    let mut len = false;
+    for constraint in constraints {
+        match constraint {
+            Constraint::Range(range) => {
+                let text = range_text(range);
+                checks.push(quote!(eclip::check_range(value, #range, #text)?;));
+            }
+            Constraint::MinLen(min) => {
+                min_len = quote!(Some(#min));
+                len = true;
+            }
+            Constraint::MaxLen(max) => {
+                max_len = quote!(Some(#max));
+                len = true;
+            }
+            Constraint::Regex(pattern) => {
+                checks.push(quote!({
+                    static PATTERN: eclip::Pattern = eclip::Pattern::new(#pattern);
+                    eclip::check_regex(value, &PATTERN)?;
+                }));
+            }
+            Constraint::PossibleValues(values) => {
+                checks.push(quote!(eclip::check_possible_values(value, &[#(#values),*])?;));
+            }
+            Constraint::ValidateWith(path) => {
+                checks.push(quote!(eclip::check_with(value, #path)?;));
+            }
+        }
+    }
+    if len {
+        checks.insert(0, quote!(eclip::check_len(value, #min_len, #max_len)?;));
+    }
+    checks
+}
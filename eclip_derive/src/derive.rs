@@ -1,26 +1,104 @@
 use quote::quote;
 use syn::DeriveInput;
 
-use crate::parser::{parse_named_fields, parse_unit, parse_unnamed_fields};
+use crate::command::CommandMeta;
+use crate::parser::parse_fields;
+use crate::value::ValueMeta;
+use crate::variant::parse_variants;
+
+/// Prints the description of a command above its usage.
+pub fn about_token(meta: &CommandMeta) -> proc_macro2::TokenStream {
+    match (
+        meta.about.as_ref().or(meta.long_about.as_ref()),
+        meta.long_about(),
+    ) {
+        (Some(about), Some(long_about)) => quote!(
+            println!("{}\n", if helper.verbose { #long_about } else { #about });
+        ),
+        _ => quote!(),
+    }
+}
 
 pub fn derive_command(input: &DeriveInput) -> proc_macro2::TokenStream {
     let target = &input.ident;
-    let (new_token, help_token) = match &input.data {
-        syn::Data::Struct(data) => match &data.fields {
-            syn::Fields::Named(fields) => parse_named_fields(fields),
-            syn::Fields::Unnamed(fields) => parse_unnamed_fields(fields),
-            syn::Fields::Unit => parse_unit(),
-        },
+    let meta = CommandMeta::from(&input.attrs);
+    let (new_token, help_token, spec_token) = match &input.data {
+        syn::Data::Struct(data) => {
+            let (new_token, help_token, spec_token) =
+                parse_fields(&data.fields, &input.attrs, &quote!(Self));
+            let about_token = about_token(&meta);
+            (new_token, quote!(#about_token #help_token), spec_token)
+        }
+        syn::Data::Enum(data) => parse_variants(data, &meta),
         syn::Data::Union(_data) => panic!("Unsupported type"),
-        syn::Data::Enum(_) => panic!("Unsupported type"),
+    };
+    let about = match &meta.about {
+        Some(about) => quote!(Some(#about)),
+        None => quote!(None),
+    };
+    let long_about = match &meta.long_about {
+        Some(long_about) => quote!(Some(#long_about.to_string())),
+        None => quote!(None),
     };
 
     quote! {
         impl eclip::Help for #target {
             fn help(helper: eclip::Helper) { #help_token }
+            fn about() -> Option<&'static str> { #about }
+            fn spec() -> eclip::CommandSpec {
+                let mut spec = #spec_token;
+                spec.about = Self::about().map(String::from);
+                spec.long_about = #long_about;
+                spec
+            }
         }
         impl eclip::ArgsNew for #target {
-            fn new(mut args: std::env::Args) -> Self { #new_token }
+            fn from_args(mut args: eclip::Args) -> Result<Self, eclip::ParseError> {
+                #new_token
+            }
+        }
+    }
+}
+
+/// Implements `Validator` for an enum of unit variants, each selected by its name or an alias.
+pub fn derive_value_enum(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let target = &input.ident;
+    let data = match &input.data {
+        syn::Data::Enum(data) => data,
+        _ => panic!("ValueEnum supports only enums"),
+    };
+    let mut names = Vec::new();
+    let mut arms = Vec::new();
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            panic!("ValueEnum supports only unit variants");
+        }
+        let ident = &variant.ident;
+        let meta = ValueMeta::from(&variant.attrs);
+        let name = meta.name(ident);
+        let aliases = meta.aliases.iter().map(|alias| alias.value());
+        arms.push(quote!(#name #(| #aliases)* => Ok(Self::#ident)));
+        names.push(name);
+    }
+    let expected = format!("one of: {}", names.join(", "));
+
+    quote! {
+        impl eclip::Validator for #target {
+            fn validate(
+                _pre: Option<Self>,
+                arg: eclip::ArgValue,
+                args: &mut eclip::Args,
+            ) -> Result<Self, eclip::ParseError> {
+                let val = arg.into_string(args)?;
+                match val.as_str() {
+                    #(#arms,)*
+                    _ => Err(eclip::ParseError::invalid_value(&val, #expected)),
+                }
+            }
+
+            fn value_hint() -> eclip::ValueHint {
+                eclip::ValueHint::Choices(&[#(#names),*])
+            }
         }
     }
 }
@@ -0,0 +1,48 @@
+use proc_macro2::Span;
+use syn::LitStr;
+
+/// Collects the `///` comments of an item into one text.
+pub fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        if !attr.path.is_ident("doc") {
+            continue;
+        }
+        if let Ok(syn::Meta::NameValue(meta)) = attr.parse_meta() {
+            if let syn::Lit::Str(lit) = meta.lit {
+                let line = lit.value();
+                lines.push(
+                    line.strip_prefix(' ')
+                        .unwrap_or(&line)
+                        .trim_end()
+                        .to_string(),
+                );
+            }
+        }
+    }
+    let doc = lines.join("\n").trim().to_string();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc)
+    }
+}
+
+/// Returns the first paragraph of a text as one line.
+pub fn first_paragraph(doc: &str) -> String {
+    doc.split("\n\n")
+        .next()
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Splits the doc comments of a field into the help and the long help.
+pub fn doc_help(attrs: &[syn::Attribute]) -> Option<(LitStr, String)> {
+    doc_comment(attrs).map(|doc| {
+        let help = LitStr::new(&first_paragraph(&doc), Span::call_site());
+        (help, doc)
+    })
+}
@@ -2,15 +2,28 @@ use proc_macro::TokenStream;
 use syn::{parse_macro_input, DeriveInput};
 
 mod argument;
+mod command;
+mod constraint;
 mod derive;
+mod doc;
 mod option;
 mod parser;
+mod relation;
 mod term;
+mod ty;
+mod value;
+mod variant;
 
-use crate::derive::derive_command;
+use crate::derive::{derive_command, derive_value_enum};
 
-#[proc_macro_derive(Command, attributes(option, argument))]
+#[proc_macro_derive(Command, attributes(command, option, argument, group))]
 pub fn command_macro_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     derive_command(&input).into()
 }
+
+#[proc_macro_derive(ValueEnum, attributes(value))]
+pub fn value_enum_macro_derive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_value_enum(&input).into()
+}
@@ -1,13 +1,43 @@
+use proc_macro2::Span;
 use syn::punctuated::Punctuated;
-use syn::{Lit, LitStr, Token};
+use syn::{Lit, LitChar, LitInt, LitStr, Token};
 
-use crate::term::Term;
+use crate::constraint::Constraint;
+use crate::doc::doc_help;
+use crate::term::{Parser, Term};
+use crate::ty::{inner, is_bool};
+
+/// The short and long keys of a `verbosity` option which lower the level of logging,
+/// with their help.
+pub const QUIET_KEYS: (&str, &str, &str) = ("q", "quiet", "Less output, repeated for each level");
+
+/// The help of the keys of a `verbosity` option which raise the level of logging.
+pub const VERBOSE_HELP: &str = "More output, repeated for each level";
 
 pub struct OptionMeta {
     pub short: Option<LitStr>,
     pub long: Option<Option<LitStr>>,
     pub default: Option<Lit>,
+    pub env: Option<LitStr>,
+    pub required: bool,
     pub help: Option<LitStr>,
+    pub long_help: Option<String>,
+    /// Converts values with `FromStr` or a function instead of `Validator`.
+    pub parser: Option<Parser>,
+    /// Checks of each value after it is converted.
+    pub constraints: Vec<Constraint>,
+    pub conflicts_with: Vec<LitStr>,
+    pub requires: Vec<LitStr>,
+    /// Makes the option required unless any of these fields is given.
+    pub required_unless: Vec<LitStr>,
+    /// The field is an `eclip::Verbosity`, raised by its keys and lowered by `-q --quiet`.
+    pub verbosity: bool,
+    /// The flag is turned off by `--no-<long>`.
+    pub negatable: bool,
+    /// Splits each value of a `Vec` field, e.g. `','` for `--tags a,b,c`.
+    pub delimiter: Option<LitChar>,
+    /// The number of values read per occurrence, e.g. `2` for `--size W H`.
+    pub num_args: Option<LitInt>,
 }
 
 impl OptionMeta {
@@ -16,7 +46,19 @@ impl OptionMeta {
             short: None,
             long: None,
             default: None,
+            env: None,
+            required: false,
             help: None,
+            long_help: None,
+            parser: None,
+            constraints: Vec::new(),
+            conflicts_with: Vec::new(),
+            requires: Vec::new(),
+            required_unless: Vec::new(),
+            verbosity: false,
+            negatable: false,
+            delimiter: None,
+            num_args: None,
         }
     }
 
@@ -24,8 +66,24 @@ impl OptionMeta {
         if attr.tokens.is_empty() {
             Self::new()
         } else {
-            attr.parse_args().unwrap()
+            attr.parse_args().unwrap_or_else(|err| panic!("{}", err))
+        }
+    }
+
+    /// Uses the doc comments of the field when `help` is not given.
+    pub fn with_doc(mut self, attrs: &[syn::Attribute]) -> Self {
+        if self.help.is_none() {
+            if let Some((help, long_help)) = doc_help(attrs) {
+                self.help = Some(help);
+                self.long_help = Some(long_help);
+            }
         }
+        self
+    }
+
+    /// Whether the option is given without a value, i.e. a `bool`, a `count` or a `Verbosity`.
+    pub fn is_flag(&self, ty: &syn::Type) -> bool {
+        is_bool(inner(ty)) || self.verbosity || matches!(self.parser, Some(Parser::Count))
     }
 
     pub fn short_key(&self) -> Option<String> {
@@ -34,11 +92,63 @@ impl OptionMeta {
             .map(|short| format!("-{}", short.value()))
     }
 
+    /// The long key, which a negatable flag always has.
     pub fn long_key(&self, name: &str) -> Option<String> {
-        self.long.as_ref().map(|long| match long {
-            Some(long) => format!("--{}", long.value()),
-            None => format!("--{}", name),
-        })
+        match &self.long {
+            Some(Some(long)) => Some(format!("--{}", long.value())),
+            Some(None) => Some(format!("--{}", name)),
+            None if self.negatable => Some(format!("--{}", name)),
+            None => None,
+        }
+    }
+
+    /// The key which turns a negatable flag off, e.g. `--no-color`.
+    pub fn negated_key(&self, name: &str) -> Option<String> {
+        if !self.negatable {
+            return None;
+        }
+        let long = self.long_key(name)?;
+        Some(format!("--no-{}", &long[2..]))
+    }
+
+    /// The keys as shown in help, e.g. `-c --[no-]color` for a negatable flag.
+    pub fn help_keys(&self, name: &str) -> String {
+        let mut keys = self.keys(name);
+        if self.negatable {
+            if let Some(long) = keys.last_mut() {
+                *long = format!("--[no-]{}", &long[2..]);
+            }
+        }
+        keys.join(" ")
+    }
+
+    /// The keys which select the option, e.g. `["-q", "--quiet"]`.
+    pub fn keys(&self, name: &str) -> Vec<String> {
+        let mut keys = Vec::new();
+        if let Some(short_key) = self.short_key() {
+            keys.push(short_key);
+        }
+        if let Some(long_key) = self.long_key(name) {
+            keys.push(long_key);
+        }
+        if keys.is_empty() {
+            keys.push(format!("--{}", name));
+        }
+        keys
+    }
+
+    /// The key which names the option in messages, preferring the long one.
+    pub fn key(&self, name: &str) -> String {
+        self.keys(name).pop().unwrap()
+    }
+
+    /// The keys which lower a `verbosity` option, besides its own keys which raise it.
+    pub fn quiet_keys(&self) -> Vec<String> {
+        if self.verbosity {
+            vec![format!("-{}", QUIET_KEYS.0), format!("--{}", QUIET_KEYS.1)]
+        } else {
+            Vec::new()
+        }
     }
 }
 
@@ -48,6 +158,12 @@ impl syn::parse::Parse for OptionMeta {
         let mut meta = OptionMeta::new();
         for term in terms.into_iter() {
             match term {
+                Term::Short(lit) if lit.value() == "h" => {
+                    return Err(syn::Error::new_spanned(
+                        lit,
+                        "short = \"h\" is reserved for -h, which shows the help",
+                    ));
+                }
                 Term::Short(lit) => {
                     meta.short = Some(lit);
                 }
@@ -57,11 +173,55 @@ impl syn::parse::Parse for OptionMeta {
                 Term::Default(lit) => {
                     meta.default = Some(lit);
                 }
+                Term::Required => {
+                    meta.required = true;
+                }
+                Term::Env(lit) => {
+                    meta.env = Some(lit);
+                }
                 Term::Help(lit) => {
                     meta.help = Some(lit);
                 }
+                Term::ParseWith => {
+                    meta.parser = Some(Parser::FromStr);
+                }
+                Term::Parser(path) => {
+                    meta.parser = Some(Parser::Path(path));
+                }
+                Term::Count => {
+                    meta.parser = Some(Parser::Count);
+                }
+                Term::Negatable => {
+                    meta.negatable = true;
+                }
+                Term::Verbosity => {
+                    meta.verbosity = true;
+                }
+                Term::Delimiter(lit) => {
+                    meta.delimiter = Some(lit);
+                }
+                Term::NumArgs(lit) => {
+                    meta.num_args = Some(lit);
+                }
+                Term::Constraint(constraint) => {
+                    meta.constraints.push(constraint);
+                }
+                Term::ConflictsWith(lit) => {
+                    meta.conflicts_with.push(lit);
+                }
+                Term::Requires(lit) => {
+                    meta.requires.push(lit);
+                }
+                Term::RequiredUnless(lit) => {
+                    meta.required_unless.push(lit);
+                }
+                _ => return Err(input.error("un support type")),
             }
         }
+        if meta.verbosity && meta.short.is_none() && meta.long.is_none() {
+            meta.short = Some(LitStr::new("v", Span::call_site()));
+            meta.long = Some(Some(LitStr::new("verbose", Span::call_site())));
+        }
         Ok(meta)
     }
 }
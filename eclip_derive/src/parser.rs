@@ -1,27 +1,197 @@
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
+use syn::{LitChar, LitStr};
 
 use crate::argument::ArgumentMeta;
-use crate::option::OptionMeta;
+use crate::constraint::{checks, choices, describe, Constraint};
+use crate::option::{OptionMeta, QUIET_KEYS, VERBOSE_HELP};
+use crate::relation::Relations;
+use crate::term::Parser;
+use crate::ty::{inner, tuple_arity, wrapper};
+
+/// How the values of a field are converted, by the `Validator` of its type,
+/// or by a function such as `FromStr::from_str` for each value.
+struct Conversion<'a> {
+    ty: &'a syn::Type,
+    parser: Option<&'a Parser>,
+    constraints: &'a [Constraint],
+    /// Splits each value of a `Vec` into several.
+    delimiter: Option<&'a LitChar>,
+}
+
+impl<'a> Conversion<'a> {
+    fn new(ty: &'a syn::Type, parser: Option<&'a Parser>, constraints: &'a [Constraint]) -> Self {
+        Self {
+            ty,
+            parser,
+            constraints,
+            delimiter: None,
+        }
+    }
+
+    fn with_delimiter(mut self, delimiter: Option<&'a LitChar>) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Converts `arg` into the field, given `pre`, the value of an earlier occurrence,
+    /// then checks every value of the field against the constraints.
+    fn validate(&self, pre: TokenStream, arg: TokenStream) -> TokenStream {
+        let convert = self.convert(pre, arg);
+        if self.constraints.is_empty() {
+            return convert;
+        }
+        self.check(convert)
+    }
+
+    /// Checks `default`, a value of the field which is used when none is given,
+    /// against the constraints, as if it were converted from a token.
+    fn validate_default(&self, default: TokenStream) -> TokenStream {
+        let ty = self.ty;
+        self.check(quote!(Ok::<#ty, eclip::ParseError>(#default)))
+    }
+
+    /// Checks every value of `converted`, the result of a conversion.
+    fn check(&self, converted: TokenStream) -> TokenStream {
+        let checks = checks(self.constraints);
+        let each = match wrapper(self.ty) {
+            Some("Option") => quote!(if let Some(value) = &converted { #(#checks)* }),
+            Some("Vec") => quote!(for value in &converted { #(#checks)* }),
+            _ => quote!(let value = &converted; #(#checks)*),
+        };
+        quote!(#converted.and_then(|converted| {
+            #each
+            Ok(converted)
+        }))
+    }
+
+    fn convert(&self, pre: TokenStream, arg: TokenStream) -> TokenStream {
+        let ty = self.ty;
+        let inner = inner(ty);
+        if let Some(delimiter) = self.delimiter {
+            if self.parser.is_some() || wrapper(ty) != Some("Vec") {
+                panic!("delimiter is only supported for a Vec converted by its Validator");
+            }
+            return quote!(eclip::split_with(#pre, #arg, &mut args, #delimiter));
+        }
+        let parser = match self.parser {
+            None => return quote!(<#ty as eclip::Validator>::validate(#pre, #arg, &mut args)),
+            Some(Parser::FromStr) => quote!(<#inner as std::str::FromStr>::from_str),
+            Some(Parser::Path(path)) => quote!(#path),
+            Some(Parser::Count) => {
+                return quote!(match #arg {
+                    eclip::ArgValue::Option(_) => Ok(#pre.unwrap_or_default().saturating_add(1)),
+                    arg => <#ty as eclip::Validator>::validate(None, arg, &mut args),
+                })
+            }
+        };
+        let expected = quote!(#inner).to_string().replace(' ', "");
+        let parse = quote!(eclip::parse_with(#arg, &mut args, #expected, #parser));
+        match wrapper(ty) {
+            Some("Option") => quote!(#parse.map(Some)),
+            Some("Vec") => quote!(#parse.map(|item| {
+                let mut list: #ty = #pre.unwrap_or_default();
+                list.push(item);
+                list
+            })),
+            _ => parse,
+        }
+    }
+
+    fn takes_value(&self) -> TokenStream {
+        let ty = self.ty;
+        if let Some(Parser::Count) = self.parser {
+            quote!(false)
+        } else if self.parser.is_some() {
+            quote!(true)
+        } else {
+            quote!(<#ty as eclip::Validator>::takes_value())
+        }
+    }
+
+    fn value_hint(&self) -> TokenStream {
+        let ty = self.ty;
+        if let Some(choices) = choices(self.constraints) {
+            quote!(eclip::ValueHint::Choices(&[#(#choices),*]))
+        } else if self.parser.is_some() {
+            quote!(eclip::ValueHint::Unknown)
+        } else {
+            quote!(<#ty as eclip::Validator>::value_hint())
+        }
+    }
+}
+
+/// How many values a variadic or trailing argument takes.
+enum Arity {
+    Fixed,
+    Varying,
+    Unbounded,
+}
 
 struct NewFactory {
     inits: Vec<TokenStream>,
     setter: Vec<TokenStream>,
+    fills: Vec<TokenStream>,
+    counters: Vec<TokenStream>,
     keys: Vec<TokenStream>,
     args: Vec<TokenStream>,
     opts: Vec<TokenStream>,
+    option_keys: Vec<String>,
+    /// Each key declared so far, with what it selects, e.g. `"quiet"`.
+    key_owners: Vec<(String, String)>,
+    takes_value: Vec<TokenStream>,
     arg_idx: syn::Index,
+    required: bool,
+    relations: Relations,
+    /// Whether each field was given on the command line,
+    /// and whether it has a value from there, the environment or the config file.
+    presence: Vec<(String, TokenStream, TokenStream)>,
+    /// Pairs of fields which cannot be given together.
+    conflicts: Vec<(String, LitStr)>,
+    /// Pairs of a field and another field which it requires.
+    requires: Vec<(String, LitStr)>,
+    /// The variadic or trailing argument, and whether it takes every remaining value
+    /// or a varying number of values up to its `max`.
+    variadic: Option<(String, Arity)>,
+    /// The index of a trailing argument which follows other arguments,
+    /// from which every token is read verbatim.
+    trailing: Option<syn::Index>,
+    /// Fields whose value from any source meets a relation, e.g. the members of a required group.
+    related: Vec<String>,
 }
 
 impl NewFactory {
-    fn new() -> Self {
+    fn new(relations: Relations) -> Self {
+        let required = relations.groups.iter().any(|group| group.required);
+        let related = relations
+            .groups
+            .iter()
+            .filter(|group| group.required)
+            .flat_map(|group| group.members.iter().map(LitStr::value))
+            .collect();
         Self {
             inits: Vec::new(),
             setter: Vec::new(),
+            fills: Vec::new(),
+            counters: Vec::new(),
             keys: Vec::new(),
             args: Vec::new(),
             opts: Vec::new(),
+            option_keys: vec!["-h".to_string(), "--help".to_string()],
+            key_owners: vec![
+                ("-h".to_string(), "the help".to_string()),
+                ("--help".to_string(), "the help".to_string()),
+            ],
+            takes_value: vec![quote!(("-h", false)), quote!(("--help", false))],
             arg_idx: syn::Index::from(0),
+            required,
+            relations,
+            presence: Vec::new(),
+            conflicts: Vec::new(),
+            requires: Vec::new(),
+            variadic: None,
+            trailing: None,
+            related,
         }
     }
 
@@ -29,78 +199,475 @@ impl NewFactory {
         self.keys.push(quote!(#ident));
     }
 
-    fn add_option(&mut self, idx: &syn::Index, name: &str, _ty: &syn::Type, meta: &OptionMeta) {
-        if let Some(default) = &meta.default {
-            self.inits.push(quote!(#default));
-        } else {
-            self.inits.push(quote!(Default::default()));
-        }
-        self.setter.push(quote!(arguments.#idx));
+    /// Reads the value of `name` from the config file, or evaluates `fallback`.
+    fn config_token(name: &str, conversion: &Conversion, fallback: TokenStream) -> TokenStream {
+        let ty = conversion.ty;
+        let validate =
+            conversion.validate(quote!(val), quote!(eclip::ArgValue::Argument(value.into())));
+        quote!(match args.config(#name)? {
+            Some(config) => {
+                let mut val = None::<#ty>;
+                for value in config.values {
+                    val = Some(#validate
+                        .map_err(|err| err.with_field(#name).with_origin(&config.origin))?);
+                }
+                val
+            }
+            None => #fallback,
+        })
+    }
+
+    /// Reads the value of `name` from the environment variable `env`, or evaluates `fallback`.
+    fn env_token(
+        name: &str,
+        env: &syn::LitStr,
+        conversion: &Conversion,
+        fallback: TokenStream,
+    ) -> TokenStream {
+        let origin = format!("environment variable {}", env.value());
+        let ty = conversion.ty;
+        let validate =
+            conversion.validate(quote!(None::<#ty>), quote!(eclip::ArgValue::Argument(val)));
+        quote!(match std::env::var_os(#env) {
+            Some(val) => Some(#validate
+                .map_err(|err| err.with_field(#name).with_origin(#origin))?),
+            None => #fallback,
+        })
+    }
 
-        let mut conditions = Vec::new();
-        if let Some(short_key) = meta.short_key() {
-            conditions.push(quote!(val == #short_key));
+    /// Fails when one of `keys` already selects something else, which would take
+    /// every occurrence of it, e.g. `-q` of a `verbosity` option and of a `quiet` field.
+    fn claim_keys(&mut self, keys: &[String], owner: String) {
+        for key in keys {
+            if let Some((_, other)) = self.key_owners.iter().find(|(used, _)| used == key) {
+                panic!("Key \"{}\" of {} is already used by {}", key, owner, other);
+            }
+            self.key_owners.push((key.clone(), owner.clone()));
         }
-        if let Some(long_key) = meta.long_key(name) {
-            conditions.push(quote!(val == #long_key));
+    }
+
+    /// Records `key` as missing, so that every missing item is reported at once.
+    fn missing_token(&mut self, key: &str) -> TokenStream {
+        self.required = true;
+        quote!({
+            missing.push(#key);
+            None
+        })
+    }
+
+    fn add_option(&mut self, idx: &syn::Index, name: &str, ty: &syn::Type, meta: &OptionMeta) {
+        self.inits.push(quote!(None::<#ty>));
+        let conversion = Conversion::new(ty, meta.parser.as_ref(), &meta.constraints)
+            .with_delimiter(meta.delimiter.as_ref());
+        let default = match &meta.default {
+            Some(default) => quote!(#default),
+            None => quote!(Default::default()),
+        };
+        let default = if meta.constraints.is_empty() {
+            quote!(Some(#default))
+        } else {
+            let validate = conversion.validate_default(default);
+            quote!(Some(#validate
+                .map_err(|err| err.with_field(#name).with_origin("the default value"))?))
+        };
+        let fallback = if meta.required {
+            self.missing_token(&meta.key(name))
+        } else if meta.required_unless.is_empty() {
+            default
+        } else {
+            self.related
+                .extend(meta.required_unless.iter().map(LitStr::value));
+            let others = meta
+                .required_unless
+                .iter()
+                .map(|other| Relations::supplied(&other.value()));
+            let missing = self.missing_token(&meta.key(name));
+            quote!(if #(#others)||* { #default } else #missing)
+        };
+        for other in &meta.conflicts_with {
+            self.conflicts.push((name.to_string(), other.clone()));
         }
-        if conditions.is_empty() {
-            let key = format!("--{}", name);
-            conditions.push(quote!(val == #key));
+        for other in &meta.requires {
+            self.requires.push((name.to_string(), other.clone()));
         }
+        let fallback = Self::config_token(name, &conversion, fallback);
+        let given = quote!(arguments.#idx.is_some());
+        let mut supplied = quote!(#given || args.config(#name)?.is_some());
+        let fallback = match &meta.env {
+            Some(env) => {
+                supplied = quote!(#supplied || std::env::var_os(#env).is_some());
+                Self::env_token(name, env, &conversion, fallback)
+            }
+            None => fallback,
+        };
+        self.presence.push((name.to_string(), given, supplied));
+        self.fills.push(quote!(
+            if arguments.#idx.is_none() {
+                arguments.#idx = #fallback;
+            }
+        ));
+        self.setter.push(quote!(arguments.#idx.unwrap()));
+
+        let mut keys = meta.keys(name);
+        let own: Vec<String> = keys.iter().cloned().chain(meta.negated_key(name)).collect();
+        self.claim_keys(&own, format!("\"{}\"", name));
+        self.claim_keys(
+            &meta.quiet_keys(),
+            format!("\"{}\" to lower the verbosity", name),
+        );
+        keys.extend(meta.quiet_keys());
+        let key = meta.key(name);
+        let conditions = keys.iter().map(|key| quote!(val == #key));
+        let arg = quote!(eclip::ArgValue::Option(val.to_string_lossy().into_owned()));
+        let assign = match &meta.num_args {
+            Some(_) if wrapper(ty) != Some("Vec") => {
+                panic!("num_args is only supported for a Vec, while a tuple reads one value per element");
+            }
+            Some(num_args) => {
+                let validate = conversion.validate(quote!(value.take()), arg);
+                quote!(
+                    let mut value = arguments.#idx.take();
+                    for _ in 0..#num_args {
+                        value = Some(#validate.map_err(|err| match err.kind() {
+                            eclip::ErrorKind::MissingValue => {
+                                eclip::ParseError::missing_values(#key, #num_args)
+                            }
+                            _ => err,
+                        }.with_field(#name))?);
+                    }
+                    arguments.#idx = value;
+                )
+            }
+            None => {
+                let validate = conversion.validate(quote!(arguments.#idx.take()), arg);
+                quote!(arguments.#idx = Some(#validate.map_err(|err| err.with_field(#name))?);)
+            }
+        };
         self.opts.push(quote!(
             if #(#conditions)||* {
-                arguments.#idx = eclip::Validator::validate(
-                    arguments.#idx, eclip::ArgValue::Option(val), &mut args
-                );
+                #assign
+                args.check_attached(#key)?;
             }
         ));
+        let takes_value = conversion.takes_value();
+        for key in &keys {
+            self.takes_value.push(quote!((#key, #takes_value)));
+        }
+        self.option_keys.extend(keys);
+        if let Some(negated) = meta.negated_key(name) {
+            let validate = conversion.validate(
+                quote!(None::<#ty>),
+                quote!(eclip::ArgValue::Argument("false".into())),
+            );
+            self.opts.push(quote!(
+                if val == #negated {
+                    arguments.#idx = Some(#validate.map_err(|err| err.with_field(#name))?);
+                    args.check_attached(#negated)?;
+                }
+            ));
+            self.takes_value.push(quote!((#negated, false)));
+            self.option_keys.push(negated);
+        }
     }
 
-    fn add_argument(
-        &mut self,
-        idx: &syn::Index,
-        _name: &str,
-        _ty: &syn::Type,
-        _meta: &ArgumentMeta,
-    ) {
-        let arg_idx = &self.arg_idx;
-        self.inits.push(quote!(None));
-        self.setter.push(quote!({
-            match arguments.#idx {
-                Some(val) => val,
-                Nome => {
-                    eprintln!("More arguments are needed.");
-                    std::process::exit(128);
-                }
-            }
-        }));
-        self.args.push(quote!(
-            if cnt == #arg_idx {
-                arguments.#idx = eclip::Validator::validate(
-                    arguments.#idx, eclip::ArgValue::Argument(val), &mut args
+    /// Fails unless every argument can be filled, i.e. there is at most one variadic
+    /// or trailing argument, none follows one which takes every remaining value,
+    /// and no required one follows one which takes a varying number of values,
+    /// since values are taken greedily up to the `max`.
+    fn check_arity(&mut self, name: &str, meta: &ArgumentMeta) {
+        if let Some((other, arity)) = &self.variadic {
+            if meta.variadic || meta.trailing {
+                panic!(
+                    "Only one variadic or trailing argument is allowed, but \"{}\" follows \"{}\"",
+                    name, other
                 );
-                cnt += 1;
             }
+            match arity {
+                Arity::Unbounded => panic!(
+                    "Argument \"{}\" follows \"{}\", which takes every remaining value",
+                    name, other
+                ),
+                Arity::Varying if !meta.optional => panic!(
+                    "Required argument \"{}\" follows \"{}\", which takes up to its max values, \
+                     so \"{}\" must be an Option",
+                    name, other, name
+                ),
+                _ => {}
+            }
+        }
+        if meta.variadic || meta.trailing {
+            let arity = match meta.max() {
+                _ if meta.trailing => Arity::Unbounded,
+                None => Arity::Unbounded,
+                Some(max) if max > meta.min() => Arity::Varying,
+                Some(_) => Arity::Fixed,
+            };
+            self.variadic = Some((name.to_string(), arity));
+        }
+    }
+
+    fn add_argument(&mut self, idx: &syn::Index, name: &str, ty: &syn::Type, meta: &ArgumentMeta) {
+        self.check_arity(name, meta);
+        self.inits.push(quote!(None::<#ty>));
+        let fallback = if meta.min() == 0 {
+            quote!(Some(Default::default()))
+        } else {
+            self.missing_token(&meta.usage(name))
+        };
+        let conversion = Conversion::new(ty, meta.parser.as_ref(), &meta.constraints);
+        let fallback = Self::config_token(name, &conversion, fallback);
+        self.presence.push((
+            name.to_string(),
+            quote!(arguments.#idx.is_some()),
+            quote!(arguments.#idx.is_some() || args.config(#name)?.is_some()),
         ));
+        let arg_idx = &self.arg_idx;
+        let validate = conversion.validate(
+            quote!(arguments.#idx.take()),
+            quote!(eclip::ArgValue::Argument(val)),
+        );
+        let validate = quote!(
+            arguments.#idx = Some(#validate.map_err(|err| err.with_field(#name))?);
+        );
+        if meta.variadic {
+            let taken = format_ident!("taken_{}", idx);
+            let min = meta.min();
+            let expected = match meta.max() {
+                Some(max) if max == min => format!("{}", min),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            self.counters.push(quote!(let mut #taken = 0;));
+            self.fills.push(quote!(
+                if arguments.#idx.is_none() {
+                    arguments.#idx = #fallback;
+                } else if #taken < #min {
+                    return Err(eclip::ParseError::value_count(#name, #expected, #taken));
+                }
+            ));
+            let next = match meta.max() {
+                Some(max) => quote!(
+                    if #taken == #max {
+                        cnt += 1;
+                    }
+                ),
+                None => quote!(),
+            };
+            self.args.push(quote!(
+                if cnt == #arg_idx {
+                    #validate
+                    #taken += 1;
+                    #next
+                }
+            ));
+        } else {
+            self.fills.push(quote!(
+                if arguments.#idx.is_none() {
+                    arguments.#idx = #fallback;
+                }
+            ));
+            if meta.trailing {
+                if arg_idx.index > 0 {
+                    self.trailing = Some(arg_idx.clone());
+                }
+                self.args.push(quote!(
+                    if cnt == #arg_idx {
+                        #validate
+                        while let Some(val) = args.next() {
+                            #validate
+                        }
+                    }
+                ));
+            } else {
+                self.args.push(quote!(
+                    if cnt == #arg_idx {
+                        #validate
+                        cnt += 1;
+                    }
+                ));
+            }
+        }
+        self.setter.push(quote!(arguments.#idx.unwrap()));
         self.arg_idx.index += 1;
     }
 
-    fn build_default_named_fields() -> TokenStream {
-        quote!(Self {})
+    /// Rejects `val`, which is neither a known option nor an expected argument.
+    fn reject_token(keys: &[String], usage: &str) -> TokenStream {
+        quote!(
+            if eclip::Args::is_option(&val) {
+                return Err(eclip::ParseError::unknown_option(&val.to_string_lossy(), &[#(#keys),*]).with_usage(#usage));
+            } else {
+                return Err(eclip::ParseError::unexpected_argument(&val.to_string_lossy()).with_usage(#usage));
+            }
+        )
     }
 
-    fn build_named_fields(self) -> TokenStream {
+    /// Stops parsing at `-h` or `--help`, so that the caller may show the help.
+    fn help_token() -> TokenStream {
+        quote!(if val == "-h" || val == "--help" {
+            return Err(eclip::ParseError::help_requested(&val.to_string_lossy()));
+        })
+    }
+
+    /// Rejects any token given to a command which takes none.
+    fn build_empty() -> TokenStream {
+        let help = Self::help_token();
+        let reject = Self::reject_token(&["-h".to_string(), "--help".to_string()], "[OPTIONS]");
+        quote!(
+            if let Some(val) = args.next() {
+                #help
+                #reject
+            }
+        )
+    }
+
+    /// Parses the tokens into `arguments`, then fills what was not given
+    /// and fails with the usage when anything required is still missing.
+    fn build_parse(&self, usage: &str) -> TokenStream {
         let opts = &self.opts;
         let args = &self.args;
-        let cond = if !opts.is_empty() && !args.is_empty() {
-            quote!(#(#opts) else * else { #(#args) else * })
-        } else if opts.is_empty() {
-            quote!(#(#args) else * )
+        let reject = Self::reject_token(&self.option_keys, usage);
+        let help = Self::help_token();
+        let takes_value = &self.takes_value;
+        let expand = quote!(
+            let val = args.expand(val, &[#(#takes_value),*]).map_err(|err| err.with_usage(#usage))?;
+        );
+        let trailing = self.trailing.iter();
+        let cond = quote!(
+            if only_args #(|| cnt == #trailing)* {
+                #(#args else)* {
+                    return Err(eclip::ParseError::unexpected_argument(&val.to_string_lossy()).with_usage(#usage));
+                }
+            } else if val == "--" {
+                only_args = true;
+            } else {
+                #expand
+                #help
+                #(#opts else)* if eclip::Args::is_option(&val) { #reject } else #(#args else)* { #reject }
+            }
+        );
+        let inits = &self.inits;
+        let counters = &self.counters;
+        let fills = &self.fills;
+        let relations = self.build_relations(usage);
+        let groups = self.build_required_groups();
+        let check = if self.required {
+            quote!(
+                let mut missing = Vec::new();
+                #(#groups)*
+                #(#fills)*
+                if !missing.is_empty() {
+                    return Err(eclip::ParseError::missing_arguments(&missing).with_usage(#usage));
+                }
+            )
         } else {
-            quote!(#(#opts) else *)
+            quote!(#(#fills)*)
         };
-        let inits = &self.inits;
+        quote! (
+            let mut arguments = ( #(#inits),*, );
+            let mut cnt = 0;
+            let mut only_args = false;
+            #(#counters)*
+            while let Some(val) = args.next() { #cond }
+            #relations
+            #check
+        )
+    }
+
+    /// Declares whether each related field was given, then fails when two conflicting fields,
+    /// or a field without its requirement, were given on the command line.
+    /// Values from the environment or the config file are defaults, which conflict with nothing,
+    /// but meet a requirement.
+    fn build_relations(&self, usage: &str) -> TokenStream {
+        let mut conflicts = self.conflicts.clone();
+        for group in self.relations.groups.iter().filter(|group| !group.multiple) {
+            for (i, member) in group.members.iter().enumerate() {
+                for other in &group.members[i + 1..] {
+                    conflicts.push((member.value(), other.clone()));
+                }
+            }
+        }
+        let mut given = Vec::new();
+        for (name, other) in &conflicts {
+            given.push(name.clone());
+            given.push(other.value());
+        }
+        let mut supplied = self.related.clone();
+        for (name, other) in &self.requires {
+            given.push(name.clone());
+            supplied.push(other.value());
+        }
+        let presence = self.presence.iter().flat_map(|(name, cli, any)| {
+            let given = given.contains(name).then(|| {
+                let ident = Relations::given(name);
+                quote!(let #ident = #cli;)
+            });
+            let supplied = supplied.contains(name).then(|| {
+                let ident = Relations::supplied(name);
+                quote!(let #ident = #any;)
+            });
+            given.into_iter().chain(supplied)
+        });
+        let conflicts = conflicts.iter().map(|(name, other)| {
+            let key = self.relations.key(name);
+            let other_key = self.relations.key(&other.value());
+            let given = Relations::given(name);
+            let other_given = Relations::given(&other.value());
+            quote!(
+                if #given && #other_given {
+                    return Err(eclip::ParseError::argument_conflict(#key, #other_key).with_usage(#usage));
+                }
+            )
+        });
+        let requires = self.requires.iter().map(|(name, other)| {
+            let key = self.relations.key(name);
+            let other_key = self.relations.key(&other.value());
+            let given = Relations::given(name);
+            let other_supplied = Relations::supplied(&other.value());
+            quote!(
+                if #given && !#other_supplied {
+                    return Err(eclip::ParseError::missing_requirement(#key, #other_key).with_usage(#usage));
+                }
+            )
+        });
+        quote!(
+            #(#presence)*
+            #(#conflicts)*
+            #(#requires)*
+        )
+    }
+
+    /// Records a required group as missing when none of its members was given.
+    fn build_required_groups(&self) -> Vec<TokenStream> {
+        self.relations
+            .groups
+            .iter()
+            .filter(|group| group.required)
+            .map(|group| {
+                let usage = group.usage(&self.relations);
+                let supplied = group
+                    .members
+                    .iter()
+                    .map(|member| Relations::supplied(&member.value()));
+                quote!(
+                    if !(#(#supplied)||*) {
+                        missing.push(#usage);
+                    }
+                )
+            })
+            .collect()
+    }
+
+    fn build_default_named_fields(ctor: &TokenStream) -> TokenStream {
+        let empty = Self::build_empty();
+        quote!(
+            #empty
+            Ok(#ctor {})
+        )
+    }
+
+    fn build_named_fields(self, ctor: &TokenStream, usage: &str) -> TokenStream {
+        let parse = self.build_parse(usage);
         let setter: Vec<TokenStream> = self
             .keys
             .iter()
@@ -108,109 +675,183 @@ impl NewFactory {
             .map(|(i, j)| quote!(#i: #j))
             .collect();
         quote! (
-            let mut arguments = ( #(#inits),*, );
-            let mut cnt = 0;
-            while let Some(val) = args.next() { #cond }
-            Self { #(#setter),* }
+            #parse
+            Ok(#ctor { #(#setter),* })
         )
     }
 
-    fn build_unit() -> TokenStream {
-        quote!(Self)
+    fn build_unit(ctor: &TokenStream) -> TokenStream {
+        let empty = Self::build_empty();
+        quote!(
+            #empty
+            Ok(#ctor)
+        )
     }
 
-    fn build_default_unnamed_fields() -> TokenStream {
-        quote!(Self())
+    fn build_default_unnamed_fields(ctor: &TokenStream) -> TokenStream {
+        let empty = Self::build_empty();
+        quote!(
+            #empty
+            Ok(#ctor())
+        )
     }
 
-    fn build_unnamed_fields(self) -> TokenStream {
-        let opts = &self.opts;
-        let args = &self.args;
-        let cond = if !opts.is_empty() && !args.is_empty() {
-            quote!(#(#opts) else * else { #(#args) else * })
-        } else if opts.is_empty() {
-            quote!(#(#args) else * )
-        } else {
-            quote!(#(#opts) else *)
-        };
-        let inits = &self.inits;
+    fn build_unnamed_fields(self, ctor: &TokenStream, usage: &str) -> TokenStream {
+        let parse = self.build_parse(usage);
         let setter = &self.setter;
         quote! (
-            let mut arguments = ( #(#inits),*, );
-            let mut cnt = 0;
-            while let Some(val) = args.next() { #cond }
-            Self ( #(#setter),* )
+            #parse
+            Ok(#ctor ( #(#setter),* ))
         )
     }
 }
 
 struct HelpFactory {
+    required: Vec<String>,
     arguments: Vec<String>,
     arg_helps: Vec<TokenStream>,
     opt_helps: Vec<TokenStream>,
 }
 
 impl HelpFactory {
-    fn new() -> Self {
+    fn new(relations: &Relations) -> Self {
         Self {
+            required: relations
+                .groups
+                .iter()
+                .filter(|group| group.required)
+                .map(|group| group.usage(relations))
+                .collect(),
             arguments: Vec::new(),
             arg_helps: Vec::new(),
             opt_helps: Vec::new(),
         }
     }
 
-    fn add_arg_help(&mut self, name: &str, meta: &ArgumentMeta) {
-        let name = format!("<{}>", name);
-        self.arg_helps.push(match &meta.help {
-            Some(help) => {
-                quote!({
-                    let name = #name;
-                    if name.len() >= helper.padding {
-                        format!("  {}\n  {:padding$} {}", name, "", #help, padding=helper.padding)
-                    } else {
-                        format!("  {:<padding$} {}", name, #help, padding=helper.padding)
-                    }
-                })
+    fn help_token(help: &Option<String>, long_help: &Option<String>) -> TokenStream {
+        match (help, long_help) {
+            (Some(help), Some(long_help)) => {
+                quote!(if helper.verbose { #long_help } else { #help })
             }
-            None => quote!(format!("  {}", #name)),
-        });
+            (Some(help), None) => quote!(#help),
+            (None, _) => quote!(""),
+        }
+    }
+
+    fn add_arg_help(&mut self, name: &str, meta: &ArgumentMeta) {
+        let name = meta.usage(name);
+        let help = meta.help.as_ref().map(|help| help.value());
+        let help = Self::help_token(&help, &meta.long_help);
+        self.arg_helps
+            .push(quote!(eclip::item_message(#name, #help, helper.padding)));
     }
 
-    fn add_opt_help(&mut self, name: &str, meta: &OptionMeta, ty: &syn::Type) {
-        let mut keys = Vec::new();
-        if let Some(short_key) = meta.short_key() {
-            keys.push(short_key);
+    /// Notes shown after the help of an option, e.g. `[env: TOKEN]` or `[requires: --password]`.
+    fn notes(name: &str, meta: &OptionMeta, relations: &Relations) -> Vec<String> {
+        let keys = |names: Vec<&LitStr>| {
+            names
+                .iter()
+                .map(|name| relations.key(&name.value()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut notes = Vec::new();
+        if let Some(env) = &meta.env {
+            notes.push(format!("[env: {}]", env.value()));
         }
-        if let Some(long_key) = meta.long_key(name) {
-            keys.push(long_key);
+        let mut conflicts: Vec<&LitStr> = meta.conflicts_with.iter().collect();
+        conflicts.extend(relations.exclusive_peers(name));
+        if !conflicts.is_empty() {
+            notes.push(format!("[conflicts with: {}]", keys(conflicts)));
         }
-        if keys.is_empty() {
-            keys.push(format!("--{}", name));
+        if !meta.requires.is_empty() {
+            notes.push(format!(
+                "[requires: {}]",
+                keys(meta.requires.iter().collect())
+            ));
         }
-        if !matches!(
-            ty,
-            syn::Type::Path(path) if path.path.segments.first().unwrap().ident == "bool"
-        ) {
-            keys.push(format!("<{}>", name.to_uppercase()));
-        }
-        let message = keys.join(" ");
-        self.opt_helps.push(match &meta.help {
-            Some(help) => {
-                quote!({
-                    let message = #message;
-                    if message.len() >= helper.padding {
-                        format!("  {}\n  {:padding$} {}", message, "", #help, padding=helper.padding)
-                    } else {
-                        format!("  {:<padding$} {}", message, #help, padding=helper.padding)
-                    }
-                })
+        if !meta.required_unless.is_empty() {
+            let others = keys(meta.required_unless.iter().collect());
+            notes.push(format!("[required unless: {}]", others));
+        }
+        notes
+    }
+
+    fn add_opt_help(
+        &mut self,
+        name: &str,
+        meta: &OptionMeta,
+        ty: &syn::Type,
+        relations: &Relations,
+    ) {
+        if meta.verbosity {
+            let keys = meta.help_keys(name);
+            let help = meta
+                .help
+                .as_ref()
+                .map_or(VERBOSE_HELP.to_string(), LitStr::value);
+            let (short, long, quiet) = QUIET_KEYS;
+            let quiet_keys = format!("-{} --{}", short, long);
+            self.opt_helps.push(quote!(
+                eclip::item_message(#keys, #help, helper.padding),
+                eclip::item_message(#quiet_keys, #quiet, helper.padding)
+            ));
+            return;
+        }
+        let keys = meta.help_keys(name);
+        let mut usage = meta.key(name);
+        let message = if meta.is_flag(ty) {
+            quote!(#keys)
+        } else {
+            let value = name.to_uppercase();
+            let num_args = match &meta.num_args {
+                Some(num_args) => num_args.base10_parse::<usize>().unwrap(),
+                None => 1,
+            };
+            let count = num_args * tuple_arity(ty).unwrap_or(1);
+            usage = format!(
+                "{} {}",
+                usage,
+                vec![format!("<{}>", value); count].join(" ")
+            );
+            match describe(&meta.constraints) {
+                Some(accepted) => {
+                    let placeholder = format!("<{}: {}>", value, accepted);
+                    let message = format!("{} {}", keys, vec![placeholder; count].join(" "));
+                    quote!(#message)
+                }
+                None => {
+                    let conversion = Conversion::new(ty, meta.parser.as_ref(), &meta.constraints);
+                    let hint = conversion.value_hint();
+                    quote!(&format!(
+                        "{} {}",
+                        #keys,
+                        vec![#hint.placeholder(#value); #count].join(" ")
+                    ))
+                }
             }
-            None => quote!(format!("  {}", #message)),
-        });
+        };
+        if meta.required {
+            self.required.push(usage);
+        }
+        let mut help = meta.help.as_ref().map(|help| help.value());
+        let mut long_help = meta.long_help.clone();
+        let notes = Self::notes(name, meta, relations);
+        if !notes.is_empty() {
+            let notes = notes.join(" ");
+            help = Some(match help {
+                Some(help) => format!("{} {}", help, notes),
+                None => notes.clone(),
+            });
+            long_help = long_help.map(|long_help| format!("{}\n{}", long_help, notes));
+        }
+        let help = Self::help_token(&help, &long_help);
+        self.opt_helps
+            .push(quote!(eclip::item_message(#message, #help, helper.padding)));
     }
 
-    fn add_argument(&mut self, name: String) {
-        self.arguments.push(format!("<{}>", name));
+    fn add_argument(&mut self, name: &str, meta: &ArgumentMeta) {
+        self.arguments.push(meta.usage(name));
     }
 
     fn build_default() -> TokenStream {
@@ -223,12 +864,16 @@ impl HelpFactory {
         )
     }
 
+    /// The usage after the command name, e.g. `[OPTIONS] --name <NAME> <a> <b>`.
+    fn usage(&self) -> String {
+        let mut usage = vec!["[OPTIONS]".to_string()];
+        usage.extend(self.required.iter().cloned());
+        usage.extend(self.arguments.iter().cloned());
+        usage.join(" ")
+    }
+
     fn build(&self) -> TokenStream {
-        let args = if self.arguments.is_empty() {
-            "".to_string()
-        } else {
-            format!(" {}", self.arguments.join(" "))
-        };
+        let usage = self.usage();
         let args_help = if self.arg_helps.is_empty() {
             quote!("")
         } else {
@@ -243,9 +888,9 @@ impl HelpFactory {
         };
         quote! (
             println!(
-                "USAGE:\n  {} [OPTIONS]{}{}{}{}",
+                "USAGE:\n  {} {}{}{}{}",
                 helper.command(),
-                #args,
+                #usage,
                 #args_help,
                 #opts_help,
                 eclip::help_message(helper.padding)
@@ -254,16 +899,145 @@ impl HelpFactory {
     }
 }
 
-pub fn parse_named_fields(fields: &syn::FieldsNamed) -> (TokenStream, TokenStream) {
+struct SpecFactory {
+    opt_specs: Vec<TokenStream>,
+    arg_specs: Vec<TokenStream>,
+}
+
+impl SpecFactory {
+    fn new() -> Self {
+        Self {
+            opt_specs: Vec::new(),
+            arg_specs: Vec::new(),
+        }
+    }
+
+    fn string_token(value: Option<String>) -> TokenStream {
+        match value {
+            Some(value) => quote!(Some(#value.to_string())),
+            None => quote!(None),
+        }
+    }
+
+    fn add_opt_spec(&mut self, name: &str, meta: &OptionMeta, ty: &syn::Type) {
+        let short = meta.short.as_ref().map(|short| short.value());
+        let long = match &meta.long {
+            Some(Some(long)) => Some(long.value()),
+            Some(None) => Some(name.to_string()),
+            None if short.is_none() || meta.negatable => Some(name.to_string()),
+            None => None,
+        };
+        let value = if meta.is_flag(ty) {
+            None
+        } else {
+            Some(name.to_uppercase())
+        };
+        let short = Self::string_token(short);
+        let long = Self::string_token(long);
+        let value = Self::string_token(value);
+        let default = Self::string_token(meta.default.as_ref().map(|default| match default {
+            syn::Lit::Str(lit) => lit.value(),
+            lit => quote!(#lit).to_string(),
+        }));
+        let required = meta.required;
+        let negatable = meta.negatable;
+        let env = Self::string_token(meta.env.as_ref().map(|env| env.value()));
+        let help = meta.help.as_ref().map(|help| help.value());
+        let help =
+            Self::string_token(help.or_else(|| meta.verbosity.then(|| VERBOSE_HELP.to_string())));
+        let long_help = Self::string_token(meta.long_help.clone());
+        let hint = Conversion::new(ty, meta.parser.as_ref(), &meta.constraints).value_hint();
+        self.opt_specs.push(quote!(eclip::OptionSpec {
+            short: #short,
+            long: #long,
+            value: #value,
+            default: #default,
+            env: #env,
+            required: #required,
+            negatable: #negatable,
+            hint: #hint,
+            help: #help,
+            long_help: #long_help,
+        }));
+        if meta.verbosity {
+            let (short, long, quiet) = QUIET_KEYS;
+            self.opt_specs.push(quote!(eclip::OptionSpec {
+                short: Some(#short.to_string()),
+                long: Some(#long.to_string()),
+                help: Some(#quiet.to_string()),
+                ..Default::default()
+            }));
+        }
+    }
+
+    fn add_arg_spec(&mut self, name: &str, meta: &ArgumentMeta, ty: &syn::Type) {
+        let help = Self::string_token(meta.help.as_ref().map(|help| help.value()));
+        let long_help = Self::string_token(meta.long_help.clone());
+        let required = meta.min() > 0;
+        let multiple = meta.variadic || meta.trailing;
+        let hint = Conversion::new(ty, meta.parser.as_ref(), &meta.constraints).value_hint();
+        self.arg_specs.push(quote!(eclip::ArgumentSpec {
+            name: #name.to_string(),
+            required: #required,
+            multiple: #multiple,
+            hint: #hint,
+            help: #help,
+            long_help: #long_help,
+        }));
+    }
+
+    fn build_default() -> TokenStream {
+        quote!(eclip::CommandSpec::default())
+    }
+
+    fn build(&self) -> TokenStream {
+        let opt_specs = &self.opt_specs;
+        let arg_specs = &self.arg_specs;
+        quote!(eclip::CommandSpec {
+            options: vec![#(#opt_specs),*],
+            arguments: vec![#(#arg_specs),*],
+            ..Default::default()
+        })
+    }
+}
+
+/// Builds the parser, help and spec of a struct or a variant with `fields`,
+/// where `attrs` are its attributes such as `#[group(...)]`.
+pub fn parse_fields(
+    fields: &syn::Fields,
+    attrs: &[syn::Attribute],
+    ctor: &TokenStream,
+) -> (TokenStream, TokenStream, TokenStream) {
+    match fields {
+        syn::Fields::Named(fields) => parse_named_fields(fields, attrs, ctor),
+        syn::Fields::Unnamed(fields) => parse_unnamed_fields(fields, attrs, ctor),
+        syn::Fields::Unit => parse_unit(ctor),
+    }
+}
+
+fn parse_named_fields(
+    fields: &syn::FieldsNamed,
+    attrs: &[syn::Attribute],
+    ctor: &TokenStream,
+) -> (TokenStream, TokenStream, TokenStream) {
     if fields.named.is_empty() {
         return (
-            NewFactory::build_default_named_fields(),
+            NewFactory::build_default_named_fields(ctor),
             HelpFactory::build_default(),
+            SpecFactory::build_default(),
         );
     }
 
-    let mut help_factory = HelpFactory::new();
-    let mut new_factory = NewFactory::new();
+    let relations = Relations::new(
+        attrs,
+        fields
+            .named
+            .iter()
+            .map(|field| (field.ident.as_ref().unwrap().to_string(), field)),
+    );
+    let mut help_factory = HelpFactory::new(&relations);
+    let mut spec_factory = SpecFactory::new();
+    let mut new_factory = NewFactory::new(relations.clone());
     let mut idx = syn::Index::from(0);
 
     for field in fields.named.iter() {
@@ -273,37 +1047,63 @@ pub fn parse_named_fields(fields: &syn::FieldsNamed) -> (TokenStream, TokenStrea
         for attr in &field.attrs {
             let attr_ident = &attr.path.segments.first().unwrap().ident;
             if attr_ident == "option" {
-                let meta = OptionMeta::from(attr);
+                let meta = OptionMeta::from(attr).with_doc(&field.attrs);
                 new_factory.add_option(&idx, &name, &field.ty, &meta);
-                help_factory.add_opt_help(&name, &meta, &field.ty);
+                help_factory.add_opt_help(&name, &meta, &field.ty, &relations);
+                spec_factory.add_opt_spec(&name, &meta, &field.ty);
                 break;
             } else if attr_ident == "argument" {
-                let meta = ArgumentMeta::from(attr);
+                let meta = ArgumentMeta::from(attr)
+                    .with_doc(&field.attrs)
+                    .with_type(&field.ty);
                 new_factory.add_argument(&idx, &name, &field.ty, &meta);
                 help_factory.add_arg_help(&name, &meta);
-                help_factory.add_argument(name);
+                spec_factory.add_arg_spec(&name, &meta, &field.ty);
+                help_factory.add_argument(&name, &meta);
                 break;
             }
         }
         idx.index += 1;
     }
 
-    (new_factory.build_named_fields(), help_factory.build())
+    (
+        new_factory.build_named_fields(ctor, &help_factory.usage()),
+        help_factory.build(),
+        spec_factory.build(),
+    )
 }
 
-pub fn parse_unit() -> (TokenStream, TokenStream) {
-    (NewFactory::build_unit(), HelpFactory::build_default())
+fn parse_unit(ctor: &TokenStream) -> (TokenStream, TokenStream, TokenStream) {
+    (
+        NewFactory::build_unit(ctor),
+        HelpFactory::build_default(),
+        SpecFactory::build_default(),
+    )
 }
 
-pub fn parse_unnamed_fields(fields: &syn::FieldsUnnamed) -> (TokenStream, TokenStream) {
+fn parse_unnamed_fields(
+    fields: &syn::FieldsUnnamed,
+    attrs: &[syn::Attribute],
+    ctor: &TokenStream,
+) -> (TokenStream, TokenStream, TokenStream) {
     if fields.unnamed.is_empty() {
         return (
-            NewFactory::build_default_unnamed_fields(),
+            NewFactory::build_default_unnamed_fields(ctor),
             HelpFactory::build_default(),
+            SpecFactory::build_default(),
         );
     }
-    let mut help_factory = HelpFactory::new();
-    let mut new_factory = NewFactory::new();
+    let relations = Relations::new(
+        attrs,
+        fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (i.to_string(), field)),
+    );
+    let mut help_factory = HelpFactory::new(&relations);
+    let mut spec_factory = SpecFactory::new();
+    let mut new_factory = NewFactory::new(relations.clone());
     let mut idx = syn::Index::from(0);
 
     for field in fields.unnamed.iter() {
@@ -311,20 +1111,28 @@ pub fn parse_unnamed_fields(fields: &syn::FieldsUnnamed) -> (TokenStream, TokenS
         for attr in &field.attrs {
             let attr_ident = &attr.path.segments.first().unwrap().ident;
             if attr_ident == "option" {
-                let meta = OptionMeta::from(attr);
+                let meta = OptionMeta::from(attr).with_doc(&field.attrs);
                 new_factory.add_option(&idx, &name, &field.ty, &meta);
-                help_factory.add_opt_help(&name, &meta, &field.ty);
+                help_factory.add_opt_help(&name, &meta, &field.ty, &relations);
+                spec_factory.add_opt_spec(&name, &meta, &field.ty);
                 break;
             } else if attr_ident == "argument" {
-                let meta = ArgumentMeta::from(attr);
+                let meta = ArgumentMeta::from(attr)
+                    .with_doc(&field.attrs)
+                    .with_type(&field.ty);
                 new_factory.add_argument(&idx, &name, &field.ty, &meta);
                 help_factory.add_arg_help(&name, &meta);
-                help_factory.add_argument(name);
+                spec_factory.add_arg_spec(&name, &meta, &field.ty);
+                help_factory.add_argument(&name, &meta);
                 break;
             }
         }
         idx.index += 1;
     }
 
-    (new_factory.build_unnamed_fields(), help_factory.build())
+    (
+        new_factory.build_unnamed_fields(ctor, &help_factory.usage()),
+        help_factory.build(),
+        spec_factory.build(),
+    )
 }
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use proc_macro2::Ident;
+use quote::format_ident;
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+
+use crate::argument::ArgumentMeta;
+use crate::option::OptionMeta;
+use crate::term::Term;
+
+/// `#[group(name = "format", members = ["json", "table"], multiple = false, required)]`,
+/// a set of fields of which only one may be given unless `multiple`,
+/// and at least one must be given when `required`.
+#[derive(Clone)]
+pub struct GroupMeta {
+    pub name: Option<LitStr>,
+    pub members: Vec<LitStr>,
+    pub multiple: bool,
+    pub required: bool,
+}
+
+impl GroupMeta {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            members: Vec::new(),
+            multiple: false,
+            required: false,
+        }
+    }
+
+    /// How a missing required group is shown, e.g. `<format: --json|--table>`.
+    pub fn usage(&self, relations: &Relations) -> String {
+        let keys = self
+            .members
+            .iter()
+            .map(|member| relations.key(&member.value()))
+            .collect::<Vec<_>>()
+            .join("|");
+        match &self.name {
+            Some(name) => format!("<{}: {}>", name.value(), keys),
+            None => format!("<{}>", keys),
+        }
+    }
+}
+
+impl syn::parse::Parse for GroupMeta {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let terms: Punctuated<Term, Token![,]> = Punctuated::parse_terminated(input)?;
+        let mut meta = GroupMeta::new();
+        for term in terms.into_iter() {
+            match term {
+                Term::Name(lit) => {
+                    meta.name = Some(lit);
+                }
+                Term::Members(members) => {
+                    meta.members = members;
+                }
+                Term::Multiple(lit) => {
+                    meta.multiple = lit.value;
+                }
+                Term::Required => {
+                    meta.required = true;
+                }
+                _ => return Err(input.error("un support type")),
+            }
+        }
+        Ok(meta)
+    }
+}
+
+/// The keys of the fields of a command and its groups,
+/// which options refer to with `conflicts_with`, `requires` and `required_unless`.
+#[derive(Clone)]
+pub struct Relations {
+    keys: HashMap<String, String>,
+    pub groups: Vec<GroupMeta>,
+}
+
+impl Relations {
+    pub fn new<'a, I>(attrs: &[syn::Attribute], fields: I) -> Self
+    where
+        I: IntoIterator<Item = (String, &'a syn::Field)>,
+    {
+        let mut keys = HashMap::new();
+        for (name, field) in fields {
+            for attr in &field.attrs {
+                if attr.path.is_ident("option") {
+                    keys.insert(name.clone(), OptionMeta::from(attr).key(&name));
+                } else if attr.path.is_ident("argument") {
+                    let meta = ArgumentMeta::from(attr).with_type(&field.ty);
+                    keys.insert(name.clone(), meta.usage(&name));
+                }
+            }
+        }
+        let groups = attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("group"))
+            .map(|attr| attr.parse_args().unwrap())
+            .collect();
+        Self { keys, groups }
+    }
+
+    /// The key which names the field `name` in messages, e.g. `--json` or `<path>`.
+    pub fn key(&self, name: &str) -> String {
+        match self.keys.get(name) {
+            Some(key) => key.clone(),
+            None => panic!("Unknown field \"{}\"", name),
+        }
+    }
+
+    /// The variable which tells whether the field `name` was given on the command line.
+    pub fn given(name: &str) -> Ident {
+        format_ident!("given_{}", name)
+    }
+
+    /// The variable which tells whether the field `name` has a value from the command line,
+    /// the environment or the config file.
+    pub fn supplied(name: &str) -> Ident {
+        format_ident!("supplied_{}", name)
+    }
+
+    /// The other members of the groups of `name` which it cannot be given with.
+    pub fn exclusive_peers(&self, name: &str) -> Vec<&LitStr> {
+        self.groups
+            .iter()
+            .filter(|group| !group.multiple)
+            .filter(|group| group.members.iter().any(|member| member.value() == name))
+            .flat_map(|group| group.members.iter())
+            .filter(|member| member.value() != name)
+            .collect()
+    }
+}
@@ -1,11 +1,46 @@
 use proc_macro2::Ident;
-use syn::{Lit, LitStr, Token};
+use syn::punctuated::Punctuated;
+use syn::{Lit, LitBool, LitChar, LitInt, LitStr, Token};
+
+use crate::constraint::Constraint;
+
+/// How the values of a field are converted when not by its `Validator`.
+pub enum Parser {
+    /// `parse_with = FromStr`
+    FromStr,
+    /// `parser = path::to::fn`, a `fn(&str) -> Result<T, E>` where `E: Display`.
+    Path(syn::Path),
+    /// `count`, the number of times a flag is given, e.g. `3` for `-vvv`.
+    Count,
+}
 
 pub enum Term {
     Short(LitStr),
     Long(Option<LitStr>),
     Default(Lit),
     Help(LitStr),
+    About(LitStr),
+    LongAbout(LitStr),
+    Env(LitStr),
+    Required,
+    Trailing,
+    Min(LitInt),
+    Max(LitInt),
+    ParseWith,
+    Parser(syn::Path),
+    Count,
+    Negatable,
+    Verbosity,
+    Delimiter(LitChar),
+    NumArgs(LitInt),
+    Name(LitStr),
+    Alias(LitStr),
+    Constraint(Constraint),
+    ConflictsWith(LitStr),
+    Requires(LitStr),
+    RequiredUnless(LitStr),
+    Members(Vec<LitStr>),
+    Multiple(LitBool),
 }
 
 impl syn::parse::Parse for Term {
@@ -26,8 +61,113 @@ impl syn::parse::Parse for Term {
         } else if ident == "help" {
             input.parse::<Token![=]>()?;
             Ok(Term::Help(input.parse()?))
+        } else if ident == "about" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::About(input.parse()?))
+        } else if ident == "long_about" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::LongAbout(input.parse()?))
+        } else if ident == "min" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Min(input.parse()?))
+        } else if ident == "max" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Max(input.parse()?))
+        } else if ident == "trailing" {
+            Ok(Term::Trailing)
+        } else if ident == "required" {
+            Ok(Term::Required)
+        } else if ident == "parse_with" {
+            input.parse::<Token![=]>()?;
+            let path: syn::Path = input.parse()?;
+            if path.is_ident("FromStr") {
+                Ok(Term::ParseWith)
+            } else {
+                Err(syn::Error::new_spanned(
+                    path,
+                    "parse_with only supports FromStr",
+                ))
+            }
+        } else if ident == "count" {
+            Ok(Term::Count)
+        } else if ident == "negatable" {
+            Ok(Term::Negatable)
+        } else if ident == "verbosity" {
+            Ok(Term::Verbosity)
+        } else if ident == "delimiter" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Delimiter(input.parse()?))
+        } else if ident == "num_args" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::NumArgs(input.parse()?))
+        } else if ident == "parser" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Parser(input.parse()?))
+        } else if ident == "range" {
+            input.parse::<Token![=]>()?;
+            match input.parse()? {
+                syn::Expr::Range(range) => Ok(Term::Constraint(Constraint::Range(range))),
+                expr => Err(syn::Error::new_spanned(expr, "range expects e.g. 1..=10")),
+            }
+        } else if ident == "min_len" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Constraint(Constraint::MinLen(input.parse()?)))
+        } else if ident == "max_len" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Constraint(Constraint::MaxLen(input.parse()?)))
+        } else if ident == "regex" {
+            input.parse::<Token![=]>()?;
+            let pattern: LitStr = input.parse()?;
+            #[cfg(feature = "regex")]
+            if let Err(err) = regex::Regex::new(&pattern.value()) {
+                return Err(syn::Error::new_spanned(
+                    pattern,
+                    format!("invalid regex: {}", err),
+                ));
+            }
+            Ok(Term::Constraint(Constraint::Regex(pattern)))
+        } else if ident == "possible_values" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Constraint(Constraint::PossibleValues(parse_list(
+                input,
+            )?)))
+        } else if ident == "conflicts_with" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::ConflictsWith(input.parse()?))
+        } else if ident == "requires" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Requires(input.parse()?))
+        } else if ident == "required_unless" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::RequiredUnless(input.parse()?))
+        } else if ident == "members" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Members(parse_list(input)?))
+        } else if ident == "multiple" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Multiple(input.parse()?))
+        } else if ident == "validate_with" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Constraint(Constraint::ValidateWith(input.parse()?)))
+        } else if ident == "name" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Name(input.parse()?))
+        } else if ident == "alias" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Alias(input.parse()?))
+        } else if ident == "env" {
+            input.parse::<Token![=]>()?;
+            Ok(Term::Env(input.parse()?))
         } else {
             Err(input.error("Unsupported type"))
         }
     }
 }
+
+/// Parses a list of strings, e.g. `["json", "table"]`.
+fn parse_list(input: syn::parse::ParseStream) -> syn::Result<Vec<LitStr>> {
+    let content;
+    syn::bracketed!(content in input);
+    let values: Punctuated<LitStr, Token![,]> = Punctuated::parse_terminated(&content)?;
+    Ok(values.into_iter().collect())
+}
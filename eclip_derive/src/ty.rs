@@ -0,0 +1,47 @@
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last(),
+        _ => None,
+    }
+}
+
+pub fn is_bool(ty: &syn::Type) -> bool {
+    matches!(last_segment(ty), Some(segment) if segment.ident == "bool")
+}
+
+/// The name of the container of `ty`, i.e. `Option` or `Vec`.
+pub fn wrapper(ty: &syn::Type) -> Option<&'static str> {
+    let segment = last_segment(ty)?;
+    if !matches!(segment.arguments, syn::PathArguments::AngleBracketed(_)) {
+        return None;
+    }
+    if segment.ident == "Option" {
+        Some("Option")
+    } else if segment.ident == "Vec" {
+        Some("Vec")
+    } else {
+        None
+    }
+}
+
+/// The type of each value of `ty`, i.e. `T` of `Option<T>` or `Vec<T>`, or `ty` itself.
+pub fn inner(ty: &syn::Type) -> &syn::Type {
+    if wrapper(ty).is_none() {
+        return ty;
+    }
+    match &last_segment(ty).unwrap().arguments {
+        syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(syn::GenericArgument::Type(inner)) => inner,
+            _ => ty,
+        },
+        _ => ty,
+    }
+}
+
+/// The number of elements of a tuple `ty`, or of the tuple inside `Option` or `Vec`.
+pub fn tuple_arity(ty: &syn::Type) -> Option<usize> {
+    match inner(ty) {
+        syn::Type::Tuple(tuple) if !tuple.elems.is_empty() => Some(tuple.elems.len()),
+        _ => None,
+    }
+}
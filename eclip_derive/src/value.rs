@@ -0,0 +1,54 @@
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+
+use crate::term::Term;
+use crate::variant::to_kebab_case;
+
+/// `#[value(...)]` of a variant of a `ValueEnum`.
+pub struct ValueMeta {
+    pub name: Option<LitStr>,
+    pub aliases: Vec<LitStr>,
+}
+
+impl ValueMeta {
+    pub fn new() -> Self {
+        Self {
+            name: None,
+            aliases: Vec::new(),
+        }
+    }
+
+    pub fn from(attrs: &[syn::Attribute]) -> Self {
+        match attrs.iter().find(|attr| attr.path.is_ident("value")) {
+            Some(attr) => attr.parse_args().unwrap(),
+            None => Self::new(),
+        }
+    }
+
+    /// The name of the variant on the command line, which is in kebab-case unless `name` is given.
+    pub fn name(&self, ident: &syn::Ident) -> String {
+        match &self.name {
+            Some(name) => name.value(),
+            None => to_kebab_case(&ident.to_string()),
+        }
+    }
+}
+
+impl syn::parse::Parse for ValueMeta {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let terms: Punctuated<Term, Token![,]> = Punctuated::parse_terminated(input)?;
+        let mut meta = ValueMeta::new();
+        for term in terms.into_iter() {
+            match term {
+                Term::Name(lit) => {
+                    meta.name = Some(lit);
+                }
+                Term::Alias(lit) => {
+                    meta.aliases.push(lit);
+                }
+                _ => return Err(input.error("un support type")),
+            }
+        }
+        Ok(meta)
+    }
+}
@@ -0,0 +1,157 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::command::CommandMeta;
+use crate::derive::about_token;
+use crate::parser::parse_fields;
+
+pub fn to_kebab_case(name: &str) -> String {
+    let mut kebab = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        } else if c == '_' {
+            kebab.push('-');
+        } else {
+            kebab.push(c);
+        }
+    }
+    kebab
+}
+
+/// Returns the type of a variant which wraps a command, e.g. `Add(AddCommand)`.
+fn wrapped_command(variant: &syn::Variant) -> Option<&syn::Type> {
+    match &variant.fields {
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+            let field = fields.unnamed.first().unwrap();
+            let has_meta = field.attrs.iter().any(|attr| {
+                let ident = &attr.path.segments.first().unwrap().ident;
+                ident == "option" || ident == "argument"
+            });
+            if has_meta {
+                None
+            } else {
+                Some(&field.ty)
+            }
+        }
+        _ => None,
+    }
+}
+
+pub fn parse_variants(
+    data: &syn::DataEnum,
+    meta: &CommandMeta,
+) -> (TokenStream, TokenStream, TokenStream) {
+    let mut new_arms = Vec::new();
+    let mut help_arms = Vec::new();
+    let mut commands = Vec::new();
+    let mut specs = Vec::new();
+
+    for variant in data.variants.iter() {
+        let ident = &variant.ident;
+        let name = to_kebab_case(&ident.to_string());
+        let meta = CommandMeta::from(&variant.attrs);
+        match wrapped_command(variant) {
+            Some(ty) => {
+                new_arms.push(quote!(
+                    #name => {
+                        args.enter(#name);
+                        <#ty as eclip::ArgsNew>::from_args(args)
+                            .map(Self::#ident)
+                            .map_err(|err| err.with_command(#name))
+                    }
+                ));
+                help_arms.push(quote!(
+                    Some(#name) => {
+                        helper.cmds.push(#name.to_string());
+                        <#ty as eclip::Help>::help(helper);
+                    }
+                ));
+                let about = match &meta.about {
+                    Some(about) => quote!(#about),
+                    None => quote!(<#ty as eclip::Help>::about().unwrap_or_default()),
+                };
+                commands.push((name.clone(), quote!((#name, #about))));
+                specs.push(quote!({
+                    let mut spec = <#ty as eclip::Help>::spec();
+                    spec.name = #name.to_string();
+                    spec.about = Some(#about.to_string()).filter(|about| !about.is_empty());
+                    spec
+                }));
+            }
+            None => {
+                let (new_token, help_token, spec_token) =
+                    parse_fields(&variant.fields, &variant.attrs, &quote!(Self::#ident));
+                let about_token = about_token(&meta);
+                new_arms.push(quote!(#name => {
+                    args.enter(#name);
+                    (|| -> Result<Self, eclip::ParseError> { #new_token })()
+                        .map_err(|err| err.with_command(#name))
+                }));
+                help_arms.push(quote!(
+                    Some(#name) => {
+                        helper.cmds.push(#name.to_string());
+                        #about_token
+                        #help_token
+                    }
+                ));
+                let long_about = match &meta.long_about {
+                    Some(long_about) => quote!(Some(#long_about.to_string())),
+                    None => quote!(None),
+                };
+                let about = meta.about.unwrap_or_default();
+                commands.push((name.clone(), quote!((#name, #about))));
+                specs.push(quote!({
+                    let mut spec = #spec_token;
+                    spec.name = #name.to_string();
+                    spec.about = Some(#about.to_string()).filter(|about| !about.is_empty());
+                    spec.long_about = #long_about;
+                    spec
+                }));
+            }
+        }
+    }
+
+    let names = commands.iter().map(|(name, _)| name);
+    let usage = "COMMAND [OPTIONS] [ARGS]...";
+    let new_token = quote!(
+        match args.next() {
+            Some(cmd) if cmd == "-h" || cmd == "--help" => {
+                Err(eclip::ParseError::help_requested(&cmd.to_string_lossy()))
+            }
+            Some(cmd) => match args
+                .find_command(&cmd.to_string_lossy(), &[#(#names),*])
+                .map_err(|err| err.with_usage(#usage))?
+            {
+                #(#new_arms,)*
+                _ => unreachable!(),
+            },
+            None => Err(eclip::ParseError::missing_command().with_usage(#usage)),
+        }
+    );
+    let about_token = about_token(meta);
+    let command_items = commands.iter().map(|(_, item)| item);
+    let help_token = quote!(
+        let mut helper = helper;
+        match helper.args.next().as_deref().and_then(std::ffi::OsStr::to_str) {
+            #(#help_arms)*
+            _ => {
+                #about_token
+                println!(
+                    "USAGE:\n  {} COMMAND [OPTIONS] [ARGS]...\n\nOPTIONS:\n{}\n\nCOMMANDS:\n{}",
+                    helper.command(),
+                    eclip::help_message(helper.padding),
+                    eclip::commands_message([#(#command_items),*], helper.padding),
+                );
+            }
+        }
+    );
+    let spec_token = quote!(eclip::CommandSpec {
+        commands: vec![#(#specs),*],
+        ..Default::default()
+    });
+    (new_token, help_token, spec_token)
+}
@@ -1,5 +1,10 @@
+use std::process::ExitCode;
+
 use eclip::{Application, Command, SubCommand};
 
+/// Run the first command.
+///
+/// Takes two integers and prints them.
 #[derive(Command, Debug)]
 #[allow(dead_code)]
 struct Command1 {
@@ -12,6 +17,8 @@ struct Command1 {
 }
 
 impl Command for Command1 {
+    type Output = ();
+
     fn run(&self) {
         println!("Run Command 1: {:?}", self)
     }
@@ -21,8 +28,11 @@ impl Command for Command1 {
 struct Command2 {}
 
 impl Command for Command2 {
-    fn run(&self) {
-        println!("Run Command 2")
+    type Output = Result<(), String>;
+
+    fn run(&self) -> Self::Output {
+        println!("Run Command 2");
+        Err("Command 2 failed".to_string())
     }
 }
 
@@ -30,6 +40,8 @@ impl Command for Command2 {
 struct Command3;
 
 impl Command for Command3 {
+    type Output = ();
+
     fn run(&self) {
         println!("Run Command 3")
     }
@@ -39,20 +51,26 @@ impl Command for Command3 {
 struct Command4();
 
 impl Command for Command4 {
+    type Output = ();
+
     fn run(&self) {
         println!("Run Command 4")
     }
 }
 
-fn main() {
+fn main() -> ExitCode {
     Application::new("ex")
-        .add_command("cmd1", Command1::entry_point)
-        .add_command("cmd2", Command2::entry_point)
-        .add_subcommand(
+        .set_about("An example of eclip.")
+        .register::<Command1>("cmd1")
+        .add_command_with_about("cmd2", "Run the second command.", Command2::entry_point)
+        .add_subcommand_with_about(
             "sub",
+            "Commands in a group.",
             SubCommand::new()
                 .add_command("cmd3", Command3::entry_point)
                 .add_command("cmd4", Command4::entry_point),
         )
-        .run();
+        .enable_completions()
+        .enable_prefix_matching()
+        .run()
 }
@@ -0,0 +1,56 @@
+use eclip::{Application, Command};
+
+/// Add a file.
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+struct Add {
+    #[option(short = "f")]
+    force: bool,
+    #[argument]
+    path: String,
+}
+
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+enum Remote {
+    /// Add a remote.
+    Add {
+        #[argument]
+        name: String,
+        #[argument]
+        url: String,
+    },
+    /// Remove a remote.
+    Remove(#[argument] String),
+    #[command(about = "List remotes.")]
+    List,
+}
+
+/// An example of subcommands declared as an enum.
+#[derive(Command, Debug)]
+enum Cli {
+    Add(Add),
+    /// Manage remotes.
+    Remote(Remote),
+    /// Show the status.
+    ShowStatus,
+    /// Run a program with the arguments which follow it.
+    Exec {
+        #[argument]
+        program: String,
+        #[argument(trailing)]
+        args: Vec<String>,
+    },
+}
+
+fn main() {
+    match Application::new("ex_enum")
+        .enable_completions()
+        .parse::<Cli>()
+    {
+        Cli::Add(add) => println!("Add: {:?}", add),
+        Cli::Remote(remote) => println!("Remote: {:?}", remote),
+        Cli::ShowStatus => println!("Status"),
+        Cli::Exec { program, args } => println!("Exec: {} {:?}", program, args),
+    }
+}
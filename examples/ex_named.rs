@@ -1,4 +1,13 @@
-use eclip::{Application, Command};
+use eclip::{Application, Command, ValueEnum};
+
+#[derive(ValueEnum, Debug, Default)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+    #[value(alias = "yml")]
+    Yaml,
+}
 
 #[derive(Command, Debug)]
 #[allow(dead_code)]
@@ -11,17 +20,30 @@ struct Command1 {
     a: i32,
     #[argument]
     b: u32,
+    /// Number of times to repeat.
+    ///
+    /// Any value below one is treated as one.
     #[option(short = "c", default = 10)]
     c: i32,
-    #[option(short = "v")]
-    v: Vec<bool>,
+    #[option(short = "v", long = "verbose", count, help = "more output")]
+    verbose: u8,
     #[option(short = "o")]
     o: Option<i32>,
     #[option(short = "f")]
     f: f32,
+    #[option(env = "EX_NAMED_TOKEN", help = "token to use")]
+    token: Option<String>,
+    #[option(env = "EX_NAMED_LEVEL", default = 3, range = 1..=5)]
+    level: u8,
+    #[option(short = "n", required, help = "name to greet")]
+    name: String,
+    #[option(help = "output format")]
+    format: Format,
 }
 
 impl Command for Command1 {
+    type Output = ();
+
     fn run(&self) {
         println!("{:?}", self);
     }
@@ -11,6 +11,8 @@ struct Command1(
 );
 
 impl Command for Command1 {
+    type Output = ();
+
     fn run(&self) {
         println!("{:?}", self);
     }
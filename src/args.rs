@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::ffi::{OsStr, OsString};
+#[cfg(feature = "config")]
+use std::rc::Rc;
+
+#[cfg(feature = "config")]
+use crate::config::Config;
+use crate::config::ConfigValue;
+use crate::error::ParseError;
+
+/// The command line tokens which are left to parse.
+/// Tokens are kept as `OsString`, so that arguments which are not valid UTF-8,
+/// such as arbitrary file names, reach their `Validator` unchanged.
+#[derive(Clone)]
+pub struct Args {
+    inner: VecDeque<OsString>,
+    /// A value given in the same token as its option, e.g. `10` of `--count=10`.
+    attached: Option<OsString>,
+    /// The names of the commands being parsed, which select the table of the config file.
+    section: Vec<String>,
+    /// Whether a command may be given by an unambiguous prefix of its name.
+    prefix_matching: bool,
+    #[cfg(feature = "config")]
+    config: Option<Rc<Config>>,
+}
+
+impl Args {
+    pub fn new<I, T>(args: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        Self {
+            inner: args.into_iter().map(Into::into).collect(),
+            attached: None,
+            section: Vec::new(),
+            prefix_matching: false,
+            #[cfg(feature = "config")]
+            config: None,
+        }
+    }
+
+    #[cfg(feature = "config")]
+    pub(crate) fn set_config(&mut self, config: Config) {
+        self.config = Some(Rc::new(config));
+    }
+
+    pub(crate) fn set_prefix_matching(&mut self, prefix_matching: bool) {
+        self.prefix_matching = prefix_matching;
+    }
+
+    /// Resolves `token` to one of `names`, or to the only one it is a prefix of
+    /// when prefix matching is enabled.
+    pub fn find_command<'n>(&self, token: &str, names: &[&'n str]) -> Result<&'n str, ParseError> {
+        if let Some(name) = names.iter().find(|name| **name == token) {
+            return Ok(name);
+        }
+        if self.prefix_matching {
+            let matches: Vec<&str> = names
+                .iter()
+                .copied()
+                .filter(|name| name.starts_with(token))
+                .collect();
+            match matches.as_slice() {
+                [] => {}
+                [name] => return Ok(name),
+                _ => return Err(ParseError::ambiguous_command(token, &matches)),
+            }
+        }
+        Err(ParseError::unknown_command(token, names))
+    }
+
+    /// Moves into the config section of the subcommand `cmd`.
+    pub fn enter(&mut self, cmd: &str) {
+        self.section.push(cmd.to_string());
+    }
+
+    /// Looks up `key` in the config section of the current command.
+    /// Always `None` without the `config` feature.
+    pub fn config(&self, key: &str) -> Result<Option<ConfigValue>, ParseError> {
+        #[cfg(feature = "config")]
+        {
+            match &self.config {
+                Some(config) => config.get(&self.section, key),
+                None => Ok(None),
+            }
+        }
+        #[cfg(not(feature = "config"))]
+        {
+            let _ = key;
+            Ok(None)
+        }
+    }
+
+    /// Whether `token` looks like an option rather than a value,
+    /// e.g. `-q` or `--quiet` but not `-` or `-1`.
+    pub fn is_option<S: AsRef<OsStr>>(token: S) -> bool {
+        let token = token.as_ref();
+        token.as_encoded_bytes().starts_with(b"-")
+            && token != "-"
+            && token
+                .to_str()
+                .is_none_or(|token| token.parse::<f64>().is_err())
+    }
+
+    /// Splits an option token which carries more than its key, and returns the key.
+    /// `keys` pairs every key of the command with whether it takes a value.
+    ///
+    /// - `--count=10` and `-c=10` attach `10` to the key, to be read by its `Validator`,
+    ///   as is `false` of `--quiet=false` for a flag.
+    /// - `-c10` attaches `10` when `-c` takes a value.
+    /// - `-qv` is read as `-q -v` when `-q` is a flag and `-v` is a key.
+    ///
+    /// Other tokens, including negative numbers such as `-5`, are returned as they are.
+    /// Fails when a flag is followed by anything but a short key, e.g. `-q5`,
+    /// which would otherwise leave `-5` to be read as an argument.
+    ///
+    /// ```
+    /// use eclip::{ArgsNew, Command, ErrorKind};
+    ///
+    /// #[derive(Command, Debug, PartialEq)]
+    /// struct Cli {
+    ///     #[option(short = "q")]
+    ///     quiet: bool,
+    ///     #[option(short = "v")]
+    ///     verbose: bool,
+    ///     #[option(short = "c", long)]
+    ///     count: i32,
+    ///     #[argument]
+    ///     n: i32,
+    /// }
+    ///
+    /// let cli = Cli::new(["-qv", "-c10", "-5"]).unwrap();
+    /// assert_eq!(cli, Cli { quiet: true, verbose: true, count: 10, n: -5 });
+    ///
+    /// let cli = Cli::new(["-c=-3", "-2"]).unwrap();
+    /// assert_eq!(cli, Cli { quiet: false, verbose: false, count: -3, n: -2 });
+    ///
+    /// let cli = Cli::new(["-vqc", "-1", "--count=7", "0"]).unwrap();
+    /// assert_eq!(cli, Cli { quiet: true, verbose: true, count: 7, n: 0 });
+    ///
+    /// let err = Cli::new(["-qx", "1"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UnknownOption);
+    /// assert_eq!(err.token(), Some("-x"));
+    ///
+    /// let err = Cli::new(["-q5"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::UnknownOption);
+    /// assert_eq!(err.token(), Some("-5"));
+    ///
+    /// let cli = Cli::new(["-q=false", "-v=true", "1"]).unwrap();
+    /// assert_eq!(cli, Cli { quiet: false, verbose: true, count: 0, n: 1 });
+    ///
+    /// let err = Cli::new(["-q=yes", "1"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    /// ```
+    pub fn expand(
+        &mut self,
+        token: OsString,
+        keys: &[(&str, bool)],
+    ) -> Result<OsString, ParseError> {
+        if !Self::is_option(&token) || keys.iter().any(|(key, _)| token == *key) {
+            return Ok(token);
+        }
+        let bytes = token.as_encoded_bytes();
+        if let Some(long) = bytes.strip_prefix(b"--") {
+            return Ok(match long.iter().position(|b| *b == b'=') {
+                Some(i) => {
+                    self.attached = Some(from_bytes(&long[i + 1..]));
+                    from_bytes(&bytes[..i + 2])
+                }
+                None => token,
+            });
+        }
+        let shorts: Vec<&str> = keys
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|key| key.chars().count() == 2 && !key.starts_with("--"))
+            .collect();
+        let found = keys
+            .iter()
+            .find(|(key, _)| shorts.contains(key) && bytes.starts_with(key.as_bytes()));
+        let (key, takes_value) = match found {
+            Some(found) => *found,
+            None => return Ok(token),
+        };
+        let rest = &bytes[key.len()..];
+        if let Some(value) = rest.strip_prefix(b"=") {
+            self.attached = Some(from_bytes(value));
+        } else if takes_value {
+            self.attached = Some(from_bytes(rest));
+        } else {
+            let mut flags = OsString::from("-");
+            flags.push(from_bytes(rest));
+            let next = shorts
+                .iter()
+                .any(|short| rest.starts_with(&short.as_bytes()[1..]));
+            if !next {
+                let keys: Vec<&str> = keys.iter().map(|(key, _)| *key).collect();
+                return Err(ParseError::unknown_option(&flags.to_string_lossy(), &keys));
+            }
+            self.inner.push_front(flags);
+        }
+        Ok(OsString::from(key))
+    }
+
+    /// Takes the value attached to the current option, e.g. `false` of `--color=false`,
+    /// for the `Validator` of a flag, which reads no other value.
+    pub fn take_attached(&mut self) -> Option<OsString> {
+        self.attached.take()
+    }
+
+    /// Fails when a value was attached to `option`, but its `Validator` did not read it.
+    pub fn check_attached(&mut self, option: &str) -> Result<(), ParseError> {
+        match self.attached.take() {
+            Some(value) => Err(ParseError::unexpected_value(
+                option,
+                &value.to_string_lossy(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn contains(&self, token: &str) -> bool {
+        self.inner.iter().any(|arg| arg == token)
+    }
+
+    pub fn peek(&self) -> Option<&OsStr> {
+        self.inner.front().map(OsString::as_os_str)
+    }
+}
+
+/// Converts a part of a token back, which `expand` only splits next to an ASCII character.
+fn from_bytes(bytes: &[u8]) -> OsString {
+    // SAFETY: `bytes` comes from `OsStr::as_encoded_bytes`, split either right after
+    // a valid UTF-8 prefix or around an ASCII `=`, which the encoding allows.
+    unsafe { OsStr::from_encoded_bytes_unchecked(bytes) }.to_os_string()
+}
+
+impl Iterator for Args {
+    type Item = OsString;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.attached.take().or_else(|| self.inner.pop_front())
+    }
+}
@@ -0,0 +1,460 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::error::ParseError;
+use crate::spec::{flatten, options, CommandSpec, OptionSpec, ValueHint};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+}
+
+impl Shell {
+    pub const NAMES: &'static [&'static str] = &["bash", "zsh", "fish", "powershell"];
+}
+
+impl FromStr for Shell {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" => Ok(Shell::PowerShell),
+            _ => Err(
+                ParseError::invalid_value(s, "bash, zsh, fish or powershell").with_field("shell"),
+            ),
+        }
+    }
+}
+
+/// Writes the completion script of `spec` for `shell`.
+pub fn generate(shell: Shell, spec: &CommandSpec, out: &mut dyn Write) -> io::Result<()> {
+    match shell {
+        Shell::Bash => bash(spec, out),
+        Shell::Zsh => zsh(spec, out),
+        Shell::Fish => fish(spec, out),
+        Shell::PowerShell => powershell(spec, out),
+    }
+}
+
+/// Names a shell function after the path of a command.
+fn id(path: &[&str]) -> String {
+    path.iter()
+        .map(|name| {
+            name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("__")
+}
+
+fn bash(spec: &CommandSpec, out: &mut dyn Write) -> io::Result<()> {
+    let mut list = Vec::new();
+    flatten(spec, vec![&spec.name], &mut list);
+
+    writeln!(out, "_{}() {{", id(&[&spec.name]))?;
+    writeln!(out, "    local cur prev cmd i opts")?;
+    writeln!(out, "    COMPREPLY=()")?;
+    writeln!(out, "    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"")?;
+    writeln!(out, "    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"")?;
+    writeln!(out, "    cmd=\"{}\"", id(&[&spec.name]))?;
+    writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do")?;
+    writeln!(out, "        case \"${{cmd}},${{COMP_WORDS[i]}}\" in")?;
+    for (path, cmd) in &list {
+        for sub in &cmd.commands {
+            let mut sub_path = path.clone();
+            sub_path.push(&sub.name);
+            writeln!(
+                out,
+                "            \"{},{}\") cmd=\"{}\" ;;",
+                id(path),
+                sub.name,
+                id(&sub_path)
+            )?;
+        }
+    }
+    writeln!(out, "        esac")?;
+    writeln!(out, "    done")?;
+    writeln!(out, "    case \"${{cmd}}\" in")?;
+    for (path, cmd) in &list {
+        let options = options(cmd, path.len() == 1);
+        let mut words: Vec<String> = options.iter().flat_map(OptionSpec::keys).collect();
+        words.extend(cmd.commands.iter().map(|sub| sub.name.clone()));
+        for argument in &cmd.arguments {
+            if let ValueHint::Choices(choices) = argument.hint {
+                words.extend(choices.iter().map(|choice| choice.to_string()));
+            }
+        }
+        writeln!(out, "        {})", id(path))?;
+        writeln!(out, "            opts=\"{}\"", words.join(" "))?;
+        let values: Vec<&OptionSpec> = options
+            .iter()
+            .filter(|option| option.value.is_some())
+            .collect();
+        if !values.is_empty() {
+            writeln!(out, "            case \"${{prev}}\" in")?;
+            for option in values {
+                let reply = match option.hint {
+                    ValueHint::Unknown | ValueHint::Path => {
+                        "COMPREPLY=($(compgen -f -- \"${cur}\"))".to_string()
+                    }
+                    ValueHint::Number => "COMPREPLY=()".to_string(),
+                    ValueHint::Choices(choices) => {
+                        format!(
+                            "COMPREPLY=($(compgen -W \"{}\" -- \"${{cur}}\"))",
+                            choices.join(" ")
+                        )
+                    }
+                };
+                writeln!(
+                    out,
+                    "                {}) {}; return 0 ;;",
+                    option.keys().join("|"),
+                    reply
+                )?;
+            }
+            writeln!(out, "            esac")?;
+        }
+        writeln!(out, "            ;;")?;
+    }
+    writeln!(out, "    esac")?;
+    writeln!(
+        out,
+        "    COMPREPLY=($(compgen -W \"${{opts}}\" -- \"${{cur}}\"))"
+    )?;
+    writeln!(out, "}}")?;
+    writeln!(
+        out,
+        "complete -F _{} -o bashdefault -o default {}",
+        id(&[&spec.name]),
+        spec.name
+    )
+}
+
+fn escape_zsh(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+        .replace('$', "\\$")
+        .replace('`', "\\`")
+}
+
+fn zsh_action(hint: ValueHint) -> String {
+    match hint {
+        ValueHint::Unknown | ValueHint::Path => "_files".to_string(),
+        ValueHint::Number => " ".to_string(),
+        ValueHint::Choices(choices) => format!("({})", choices.join(" ")),
+    }
+}
+
+fn zsh(spec: &CommandSpec, out: &mut dyn Write) -> io::Result<()> {
+    let mut list = Vec::new();
+    flatten(spec, vec![&spec.name], &mut list);
+
+    writeln!(out, "#compdef {}", spec.name)?;
+    for (path, cmd) in &list {
+        writeln!(out)?;
+        writeln!(out, "_{}() {{", id(path))?;
+        writeln!(out, "    local context state state_descr line")?;
+        writeln!(out, "    typeset -A opt_args")?;
+        let mut specs = Vec::new();
+        for option in options(cmd, path.len() == 1) {
+            let keys = option.keys();
+            let help = escape_zsh(option.help.as_deref().unwrap_or_default());
+            let value = match &option.value {
+                Some(value) => format!(":{}:{}", escape_zsh(value), zsh_action(option.hint)),
+                None => String::new(),
+            };
+            if keys.len() > 1 {
+                specs.push(format!(
+                    "'({})'{{{}}}'[{}]{}'",
+                    keys.join(" "),
+                    keys.join(","),
+                    help,
+                    value
+                ));
+            } else {
+                specs.push(format!("'{}[{}]{}'", keys[0], help, value));
+            }
+        }
+        for argument in &cmd.arguments {
+            let prefix = match (argument.multiple, argument.required) {
+                (true, _) => "*:",
+                (false, true) => ":",
+                (false, false) => "::",
+            };
+            specs.push(format!(
+                "'{}{}:{}'",
+                prefix,
+                escape_zsh(&argument.name),
+                zsh_action(argument.hint)
+            ));
+        }
+        if !cmd.commands.is_empty() {
+            specs.push("':command:->command'".to_string());
+            specs.push("'*::arg:->args'".to_string());
+        }
+        write!(out, "    _arguments -C")?;
+        for spec in specs {
+            write!(out, " \\\n        {}", spec)?;
+        }
+        writeln!(out)?;
+        if !cmd.commands.is_empty() {
+            writeln!(out, "    case $state in")?;
+            writeln!(out, "        command)")?;
+            writeln!(out, "            local -a commands")?;
+            writeln!(out, "            commands=(")?;
+            for sub in &cmd.commands {
+                let about = escape_zsh(sub.about.as_deref().unwrap_or_default());
+                writeln!(out, "                '{}:{}'", escape_zsh(&sub.name), about)?;
+            }
+            writeln!(out, "            )")?;
+            writeln!(out, "            _describe -t commands 'command' commands")?;
+            writeln!(out, "            ;;")?;
+            writeln!(out, "        args)")?;
+            writeln!(out, "            case $words[1] in")?;
+            for sub in &cmd.commands {
+                let mut sub_path = path.clone();
+                sub_path.push(&sub.name);
+                writeln!(out, "                {}) _{} ;;", sub.name, id(&sub_path))?;
+            }
+            writeln!(out, "            esac")?;
+            writeln!(out, "            ;;")?;
+            writeln!(out, "    esac")?;
+        }
+        writeln!(out, "}}")?;
+    }
+    writeln!(out)?;
+    writeln!(out, "_{} \"$@\"", id(&[&spec.name]))
+}
+
+fn escape_fish(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish(spec: &CommandSpec, out: &mut dyn Write) -> io::Result<()> {
+    let mut list = Vec::new();
+    flatten(spec, Vec::new(), &mut list);
+
+    for (path, cmd) in &list {
+        let mut conditions: Vec<String> = path
+            .iter()
+            .map(|name| format!("__fish_seen_subcommand_from {}", name))
+            .collect();
+        if !cmd.commands.is_empty() {
+            let names: Vec<&str> = cmd.commands.iter().map(|sub| sub.name.as_str()).collect();
+            conditions.push(format!(
+                "not __fish_seen_subcommand_from {}",
+                names.join(" ")
+            ));
+        }
+        let condition = if path.is_empty() && !cmd.commands.is_empty() {
+            " -n '__fish_use_subcommand'".to_string()
+        } else if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" -n '{}'", conditions.join("; and "))
+        };
+
+        for option in options(cmd, path.is_empty()) {
+            let mut line = format!("complete -c {}{}", spec.name, condition);
+            if let Some(short) = &option.short {
+                line.push_str(&format!(" -s {}", short));
+            }
+            if let Some(long) = &option.long {
+                line.push_str(&format!(" -l {}", long));
+                if option.negatable {
+                    writeln!(out, "complete -c {}{} -l no-{}", spec.name, condition, long)?;
+                }
+            }
+            if let Some(help) = &option.help {
+                line.push_str(&format!(" -d '{}'", escape_fish(help)));
+            }
+            if option.value.is_some() {
+                match option.hint {
+                    ValueHint::Unknown | ValueHint::Path => line.push_str(" -r -F"),
+                    ValueHint::Number => line.push_str(" -x"),
+                    ValueHint::Choices(choices) => {
+                        line.push_str(&format!(" -x -a '{}'", choices.join(" ")))
+                    }
+                }
+            }
+            writeln!(out, "{}", line)?;
+        }
+        for argument in &cmd.arguments {
+            if let ValueHint::Choices(choices) = argument.hint {
+                let mut line = format!(
+                    "complete -c {}{} -f -a '{}'",
+                    spec.name,
+                    condition,
+                    choices.join(" ")
+                );
+                if let Some(help) = &argument.help {
+                    line.push_str(&format!(" -d '{}'", escape_fish(help)));
+                }
+                writeln!(out, "{}", line)?;
+            }
+        }
+        for sub in &cmd.commands {
+            let mut line = format!(
+                "complete -c {}{} -f -a '{}'",
+                spec.name, condition, sub.name
+            );
+            if let Some(about) = &sub.about {
+                line.push_str(&format!(" -d '{}'", escape_fish(about)));
+            }
+            writeln!(out, "{}", line)?;
+        }
+    }
+    Ok(())
+}
+
+fn escape_powershell(text: &str) -> String {
+    text.replace('\'', "''")
+}
+
+fn powershell(spec: &CommandSpec, out: &mut dyn Write) -> io::Result<()> {
+    let mut list = Vec::new();
+    flatten(spec, vec![&spec.name], &mut list);
+    let result = "[System.Management.Automation.CompletionResult]";
+    let result_type = "[System.Management.Automation.CompletionResultType]";
+
+    writeln!(
+        out,
+        "Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{",
+        escape_powershell(&spec.name)
+    )?;
+    writeln!(
+        out,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    )?;
+    writeln!(out)?;
+    writeln!(out, "    $commandElements = $commandAst.CommandElements")?;
+    writeln!(out, "    $command = @(")?;
+    writeln!(out, "        '{}'", escape_powershell(&spec.name))?;
+    writeln!(
+        out,
+        "        for ($i = 1; $i -lt $commandElements.Count; $i++) {{"
+    )?;
+    writeln!(out, "            $element = $commandElements[$i]")?;
+    writeln!(out, "            if ($element -isnot [System.Management.Automation.Language.StringConstantExpressionAst] -or")?;
+    writeln!(out, "                $element.StringConstantType -ne [System.Management.Automation.Language.StringConstantType]::BareWord -or")?;
+    writeln!(out, "                $element.Value.StartsWith('-') -or")?;
+    writeln!(
+        out,
+        "                $element.Value -eq $wordToComplete) {{"
+    )?;
+    writeln!(out, "                break")?;
+    writeln!(out, "            }}")?;
+    writeln!(out, "            $element.Value")?;
+    writeln!(out, "        }}) -join ';'")?;
+    writeln!(
+        out,
+        "    $previous = \"$(@($commandElements | Where-Object {{ $_.Extent.EndOffset -lt $cursorPosition }})[-1])\""
+    )?;
+    writeln!(out)?;
+    // The value of an option; returning nothing falls back to the names of files.
+    writeln!(out, "    switch (\"$command;$previous\") {{")?;
+    for (path, cmd) in &list {
+        for option in options(cmd, path.len() == 1) {
+            if option.value.is_none() {
+                continue;
+            }
+            let cases: Vec<String> = option
+                .keys()
+                .iter()
+                .map(|key| {
+                    let case = format!("{};{}", path.join(";"), key);
+                    format!("'{}'", escape_powershell(&case))
+                })
+                .collect();
+            let reply = match option.hint {
+                ValueHint::Unknown | ValueHint::Path | ValueHint::Number => "return".to_string(),
+                ValueHint::Choices(choices) => {
+                    let choices: Vec<String> = choices
+                        .iter()
+                        .map(|choice| format!("'{}'", escape_powershell(choice)))
+                        .collect();
+                    format!(
+                        "return @({}).Where{{ $_ -like \"$wordToComplete*\" }}.ForEach{{ {}::new($_, $_, {}::ParameterValue, $_) }}",
+                        choices.join(", "),
+                        result,
+                        result_type
+                    )
+                }
+            };
+            writeln!(
+                out,
+                "        {{ $_ -in {} }} {{ {} }}",
+                cases.join(", "),
+                reply
+            )?;
+        }
+    }
+    writeln!(out, "    }}")?;
+    writeln!(out)?;
+    writeln!(out, "    $completions = @(switch ($command) {{")?;
+    for (path, cmd) in &list {
+        writeln!(out, "        '{}' {{", escape_powershell(&path.join(";")))?;
+        for option in options(cmd, path.len() == 1) {
+            let help = option.help.as_deref().unwrap_or_default();
+            for key in option.keys() {
+                let help = if help.is_empty() { key.as_str() } else { help };
+                writeln!(
+                    out,
+                    "            {}::new('{}', '{}', {}::ParameterName, '{}')",
+                    result,
+                    key,
+                    key.trim_start_matches('-'),
+                    result_type,
+                    escape_powershell(help)
+                )?;
+            }
+        }
+        for argument in &cmd.arguments {
+            if let ValueHint::Choices(choices) = argument.hint {
+                for choice in choices {
+                    writeln!(
+                        out,
+                        "            {}::new('{}', '{}', {}::ParameterValue, '{}')",
+                        result,
+                        escape_powershell(choice),
+                        escape_powershell(choice),
+                        result_type,
+                        escape_powershell(choice)
+                    )?;
+                }
+            }
+        }
+        for sub in &cmd.commands {
+            let about = sub.about.as_deref().unwrap_or(&sub.name);
+            writeln!(
+                out,
+                "            {}::new('{}', '{}', {}::ParameterValue, '{}')",
+                result,
+                escape_powershell(&sub.name),
+                escape_powershell(&sub.name),
+                result_type,
+                escape_powershell(about)
+            )?;
+        }
+        writeln!(out, "            break")?;
+        writeln!(out, "        }}")?;
+    }
+    writeln!(out, "    }})")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "    $completions.Where{{ $_.CompletionText -like \"$wordToComplete*\" }} |"
+    )?;
+    writeln!(out, "        Sort-Object -Property ListItemText")?;
+    writeln!(out, "}}")
+}
@@ -0,0 +1,99 @@
+#[cfg(feature = "config")]
+use std::fs;
+#[cfg(feature = "config")]
+use std::io;
+#[cfg(feature = "config")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "config")]
+use crate::error::ParseError;
+
+/// The values of a key in a config file, which are converted with a `Validator`
+/// as if they were given on the command line.
+pub struct ConfigValue {
+    pub values: Vec<String>,
+    /// Where the values came from, e.g. `key remote.add.url in config.toml`.
+    pub origin: String,
+}
+
+/// Defaults read from a TOML file, with a table for each command path,
+/// e.g. `[remote.add]` for `app remote add`.
+#[cfg(feature = "config")]
+pub(crate) struct Config {
+    path: PathBuf,
+    table: toml::Table,
+}
+
+#[cfg(feature = "config")]
+impl Config {
+    /// Reads `path`, or returns `None` when the file does not exist.
+    pub(crate) fn load(path: &Path) -> Result<Option<Self>, ParseError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(ParseError::invalid_config(
+                    &path.display().to_string(),
+                    &err.to_string(),
+                ))
+            }
+        };
+        let table = content.parse().map_err(|err: toml::de::Error| {
+            ParseError::invalid_config(&path.display().to_string(), err.message())
+        })?;
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            table,
+        }))
+    }
+
+    /// Fails when the section is not a table, or the value is a nested array or a table,
+    /// which cannot be given on the command line either.
+    pub(crate) fn get(
+        &self,
+        section: &[String],
+        key: &str,
+    ) -> Result<Option<ConfigValue>, ParseError> {
+        let mut table = &self.table;
+        for (i, name) in section.iter().enumerate() {
+            table = match table.get(name) {
+                Some(toml::Value::Table(table)) => table,
+                Some(_) => return Err(self.invalid(&section[..=i], "expected a table")),
+                None => return Ok(None),
+            };
+        }
+        let values = match table.get(key) {
+            Some(toml::Value::Array(array)) => array.iter().map(to_string).collect(),
+            Some(value) => to_string(value).map(|value| vec![value]),
+            None => return Ok(None),
+        };
+        let mut path = section.to_vec();
+        path.push(key.to_string());
+        match values {
+            Some(values) => Ok(Some(ConfigValue {
+                values,
+                origin: self.origin(&path),
+            })),
+            None => Err(self.invalid(&path, "expected a value or an array of values")),
+        }
+    }
+
+    /// Describes the key at `path`, e.g. `key remote.add.url in config.toml`.
+    fn origin(&self, path: &[String]) -> String {
+        format!("key {} in {}", path.join("."), self.path.display())
+    }
+
+    fn invalid(&self, path: &[String], message: &str) -> ParseError {
+        ParseError::invalid_config(&self.path.display().to_string(), message)
+            .with_origin(&self.origin(path))
+    }
+}
+
+#[cfg(feature = "config")]
+fn to_string(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+        value => Some(value.to_string()),
+    }
+}
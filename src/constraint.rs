@@ -0,0 +1,99 @@
+use std::fmt::Display;
+use std::ops::RangeBounds;
+#[cfg(feature = "regex")]
+use std::sync::OnceLock;
+
+use crate::error::ParseError;
+
+/// `range = 1..=65535`, where `expected` is how the range is shown, e.g. `1-65535`.
+pub fn check_range<T, R>(value: &T, range: R, expected: &str) -> Result<(), ParseError>
+where
+    T: PartialOrd + Display,
+    R: RangeBounds<T>,
+{
+    if range.contains(value) {
+        Ok(())
+    } else {
+        Err(ParseError::invalid_value(&value.to_string(), expected))
+    }
+}
+
+/// `min_len` and `max_len`, counted in characters.
+pub fn check_len<T: AsRef<str>>(
+    value: &T,
+    min: Option<usize>,
+    max: Option<usize>,
+) -> Result<(), ParseError> {
+    let value = value.as_ref();
+    let len = value.chars().count();
+    if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+        let expected = match (min, max) {
+            (Some(min), Some(max)) => format!("{} to {} characters", min, max),
+            (Some(min), None) => format!("at least {} characters", min),
+            (None, _) => format!("at most {} characters", max.unwrap_or_default()),
+        };
+        Err(ParseError::invalid_value(value, &expected))
+    } else {
+        Ok(())
+    }
+}
+
+/// `possible_values = ["a", "b"]`.
+pub fn check_possible_values<T: AsRef<str>>(value: &T, values: &[&str]) -> Result<(), ParseError> {
+    let value = value.as_ref();
+    if values.contains(&value) {
+        Ok(())
+    } else {
+        let expected = format!("one of: {}", values.join(", "));
+        Err(ParseError::invalid_value(value, &expected))
+    }
+}
+
+/// The pattern of `regex = "..."`, compiled on first use and reused for every value after.
+/// The derive checks the pattern at compile time, and declares one `static` per field.
+#[cfg(feature = "regex")]
+pub struct Pattern {
+    source: &'static str,
+    regex: OnceLock<regex::Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl Pattern {
+    pub const fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    /// # Panics
+    ///
+    /// Panics when the pattern is not a valid regular expression.
+    fn regex(&self) -> &regex::Regex {
+        self.regex.get_or_init(|| {
+            regex::Regex::new(self.source)
+                .unwrap_or_else(|err| panic!("invalid regex {:?}: {}", self.source, err))
+        })
+    }
+}
+
+/// `regex = "..."`, which must match somewhere in the value unless anchored.
+#[cfg(feature = "regex")]
+pub fn check_regex<T: AsRef<str>>(value: &T, pattern: &Pattern) -> Result<(), ParseError> {
+    let value = value.as_ref();
+    if pattern.regex().is_match(value) {
+        Ok(())
+    } else {
+        let expected = format!("a value matching \"{}\"", pattern.source);
+        Err(ParseError::invalid_value(value, &expected))
+    }
+}
+
+/// `validate_with = path::to::fn`, where the function returns the reason of a rejection.
+pub fn check_with<T, E, F>(value: &T, check: F) -> Result<(), ParseError>
+where
+    E: Display,
+    F: FnOnce(&T) -> Result<(), E>,
+{
+    check(value).map_err(|err| ParseError::rejected_value(&err.to_string()))
+}
@@ -1,28 +1,77 @@
 use std::collections::BTreeMap;
 use std::env;
-use std::process::exit;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+#[cfg(feature = "config")]
+use std::path::PathBuf;
+use std::process::{exit, ExitCode, Termination};
 
-use crate::utils::help_message;
+use crate::args::Args;
+use crate::completion::{self, Shell};
+#[cfg(feature = "config")]
+use crate::config::Config;
+use crate::error::{ErrorKind, ParseError, EXIT_STATUS};
+use crate::man::{self, ManPage};
+use crate::spec::{ArgumentSpec, CommandSpec, ValueHint};
+use crate::utils::{commands_message, help_message, item_message};
 
-pub trait ArgsNew {
-    fn new(args: env::Args) -> Self;
+pub trait ArgsNew: Sized {
+    fn from_args(args: Args) -> Result<Self, ParseError>;
+
+    /// Parses the tokens which follow the command name, e.g. `["-q", "1", "2"]`.
+    fn new<I, T>(args: I) -> Result<Self, ParseError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        Self::from_args(Args::new(args))
+    }
 }
 
 pub trait Help {
     fn help(helper: Helper);
+
+    /// A one-line description shown next to the command name under COMMANDS.
+    fn about() -> Option<&'static str> {
+        None
+    }
+
+    /// Describes the options and arguments of the command, e.g. for shell completions.
+    fn spec() -> CommandSpec {
+        CommandSpec::default()
+    }
 }
 
 pub trait Command {
-    fn run(&self);
-    fn entry_point(helper: Helper)
+    /// The result of [`Command::run`], which becomes the exit status of the process.
+    type Output: Termination;
+
+    fn run(&self) -> Self::Output;
+    fn entry_point(mut helper: Helper) -> ExitCode
     where
         Self: Sized + ArgsNew,
         Self: Help,
     {
         if helper.help {
             <Self as Command>::help(helper);
+            ExitCode::SUCCESS
         } else {
-            Self::new(helper.args).run();
+            let command = helper.command();
+            let mut args = helper.args.clone();
+            for cmd in &helper.cmds {
+                args.enter(cmd);
+            }
+            match Self::from_args(args) {
+                Ok(cmd) => cmd.run().report(),
+                Err(err) if err.kind() == ErrorKind::HelpRequested => {
+                    helper.verbose = err.token() == Some("--help");
+                    <Self as Command>::help(helper);
+                    ExitCode::SUCCESS
+                }
+                Err(err) => err.with_command(&command).report(),
+            }
         }
     }
     fn help(helper: Helper)
@@ -34,13 +83,80 @@ pub trait Command {
 }
 
 enum Runner<'a> {
-    FType(fn(Helper)),
+    FType(fn(Helper) -> ExitCode, fn() -> CommandSpec),
     MType(SubCommand<'a>),
 }
 
+impl<'a> Runner<'a> {
+    fn run(&self, helper: Helper) -> ExitCode {
+        match self {
+            Runner::FType(cmd, _) => cmd(helper),
+            Runner::MType(cmd) => cmd.run(helper),
+        }
+    }
+
+    fn spec(&self, name: &str, about: &str) -> CommandSpec {
+        let mut spec = match self {
+            Runner::FType(_, spec) => spec(),
+            Runner::MType(cmd) => cmd.spec(),
+        };
+        spec.name = name.to_string();
+        if !about.is_empty() {
+            spec.about = Some(about.to_string());
+        }
+        spec
+    }
+}
+
+/// What to do with the token in place of a command name.
+enum Next<'n> {
+    Command(&'n str),
+    Help,
+    Exit(ExitCode),
+}
+
+/// Reads the name of the command to run, or shows the help when none is given.
+/// `--version` in place of the name prints the version.
+fn next_command<'n>(helper: &mut Helper, names: &[&'n str]) -> Next<'n> {
+    let token = match helper.args.next() {
+        Some(token) => token.to_string_lossy().into_owned(),
+        None => return Next::Help,
+    };
+    if token == "--version" {
+        println!("{}", helper.version);
+        return Next::Exit(ExitCode::SUCCESS);
+    }
+    if token == "-h" || token == "--help" {
+        helper.help = true;
+        helper.verbose = token == "--help";
+        return Next::Help;
+    }
+    match helper.args.find_command(&token, names) {
+        Ok(name) => {
+            helper.cmds.push(name.to_string());
+            Next::Command(name)
+        }
+        Err(_) if helper.help => Next::Help,
+        Err(err) => {
+            let err = if Args::is_option(&token) {
+                ParseError::unknown_option(&token, &["-h", "--help", "--version"])
+            } else {
+                err
+            };
+            Next::Exit(
+                err.with_usage("COMMAND [OPTIONS] [ARGS]...")
+                    .with_command(&helper.command())
+                    .report(),
+            )
+        }
+    }
+}
+
 pub struct Helper<'a> {
-    pub args: env::Args,
+    pub args: Args,
     pub help: bool,
+    /// Whether `--help` was given rather than `-h`, which shows the long descriptions.
+    pub verbose: bool,
     pub name: &'a str,
     pub cmds: Vec<String>,
     pub version: &'a str,
@@ -48,16 +164,11 @@ pub struct Helper<'a> {
 }
 
 impl<'a> Helper<'a> {
-    pub fn new(
-        args: env::Args,
-        name: &'a str,
-        version: &'a str,
-        help: bool,
-        padding: usize,
-    ) -> Self {
+    pub fn new(args: Args, name: &'a str, version: &'a str, help: bool, padding: usize) -> Self {
         Self {
             args,
             help,
+            verbose: false,
             name,
             cmds: Vec::new(),
             version,
@@ -75,10 +186,15 @@ impl<'a> Helper<'a> {
 }
 
 pub struct Application<'a> {
-    cmds: BTreeMap<&'a str, Runner<'a>>,
+    cmds: BTreeMap<&'a str, (&'a str, Runner<'a>)>,
     name: &'a str,
+    about: &'a str,
     version: &'a str,
     padding: usize,
+    completions: bool,
+    prefix_matching: bool,
+    #[cfg(feature = "config")]
+    config: Option<PathBuf>,
 }
 
 impl<'a> Application<'a> {
@@ -86,8 +202,13 @@ impl<'a> Application<'a> {
         Self {
             cmds: BTreeMap::new(),
             name,
+            about: "",
             version: "",
             padding: 30,
+            completions: false,
+            prefix_matching: false,
+            #[cfg(feature = "config")]
+            config: None,
         }
     }
 
@@ -101,63 +222,361 @@ impl<'a> Application<'a> {
         self
     }
 
-    pub fn add_command(mut self, name: &'a str, cmd: fn(Helper)) -> Self {
-        self.cmds.insert(name, Runner::FType(cmd));
+    pub fn set_about(mut self, about: &'a str) -> Self {
+        self.about = about;
+        self
+    }
+
+    /// Adds a built-in `completions <shell>` command which prints a completion script.
+    pub fn enable_completions(mut self) -> Self {
+        self.completions = true;
+        self
+    }
+
+    /// Reads defaults of options and arguments from a TOML file, if it exists.
+    /// Top-level keys belong to the root command and a table to each subcommand,
+    /// e.g. `[remote.add]`. Values given on the command line or in the environment win.
+    #[cfg(feature = "config")]
+    pub fn set_config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config = Some(path.into());
+        self
+    }
+
+    /// Reads defaults from `$XDG_CONFIG_HOME/<name>/config.toml`,
+    /// or `~/.config/<name>/config.toml`, as with [`Application::set_config`].
+    #[cfg(feature = "config")]
+    pub fn enable_config(self) -> Self {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+        match dir {
+            Some(dir) => {
+                let path = dir.join(self.name).join("config.toml");
+                self.set_config(path)
+            }
+            None => self,
+        }
+    }
+
+    /// Accepts an unambiguous prefix of a command name, e.g. `st` for `status`.
+    pub fn enable_prefix_matching(mut self) -> Self {
+        self.prefix_matching = true;
+        self
+    }
+
+    pub fn add_command(self, name: &'a str, cmd: fn(Helper) -> ExitCode) -> Self {
+        self.add_command_with_about(name, "", cmd)
+    }
+
+    pub fn add_command_with_about(
+        mut self,
+        name: &'a str,
+        about: &'a str,
+        cmd: fn(Helper) -> ExitCode,
+    ) -> Self {
+        self.cmds
+            .insert(name, (about, Runner::FType(cmd, CommandSpec::default)));
+        self
+    }
+
+    /// Adds a derived command, so that its description and options are known to
+    /// the help and shell completions.
+    pub fn register<T>(mut self, name: &'a str) -> Self
+    where
+        T: Command + ArgsNew + Help,
+    {
+        let about = T::about().unwrap_or_default();
+        self.cmds
+            .insert(name, (about, Runner::FType(T::entry_point, T::spec)));
         self
     }
 
-    pub fn add_subcommand(mut self, name: &'a str, subcmd: SubCommand<'a>) -> Self {
-        self.cmds.insert(name, Runner::MType(subcmd));
+    pub fn add_subcommand(self, name: &'a str, subcmd: SubCommand<'a>) -> Self {
+        self.add_subcommand_with_about(name, "", subcmd)
+    }
+
+    pub fn add_subcommand_with_about(
+        mut self,
+        name: &'a str,
+        about: &'a str,
+        subcmd: SubCommand<'a>,
+    ) -> Self {
+        self.cmds.insert(name, (about, Runner::MType(subcmd)));
         self
     }
 
     fn help(&self, helper: Helper) {
+        if !self.about.is_empty() {
+            println!("{}\n", self.about);
+        }
         println!(
-            "USAGE:\n  {} COMMAND [OPTIONS] [ARGS]...\n\nOPTIONS:\n{}\n\nCOMMANDS:",
+            "USAGE:\n  {} COMMAND [OPTIONS] [ARGS]...\n\nOPTIONS:\n{}\n\nCOMMANDS:\n{}",
             helper.name,
             help_message(helper.padding),
+            commands_message(
+                self.cmds
+                    .iter()
+                    .map(|(name, (about, _))| (*name, *about))
+                    .chain(
+                        self.completions
+                            .then_some(("completions", COMPLETIONS_ABOUT))
+                    ),
+                helper.padding
+            ),
         );
-        for cmd in self.cmds.keys() {
-            println!("  {}", cmd)
+    }
+
+    /// Describes the whole command tree of the application.
+    pub fn spec(&self) -> CommandSpec {
+        let mut spec = if self.cmds.len() == 1 {
+            let (about, runner) = self.cmds.values().next().unwrap();
+            runner.spec(self.name, about)
+        } else {
+            let mut spec = CommandSpec::new(self.name);
+            spec.commands = self
+                .cmds
+                .iter()
+                .map(|(name, (about, runner))| runner.spec(name, about))
+                .collect();
+            spec
+        };
+        if !self.about.is_empty() {
+            spec.about = Some(self.about.to_string());
         }
+        if self.completions {
+            spec.commands.push(completions_spec());
+        }
+        spec
+    }
+
+    /// Writes a completion script of the application for `shell`.
+    pub fn generate_completions(&self, shell: Shell, out: &mut dyn Write) -> io::Result<()> {
+        completion::generate(shell, &self.spec(), out)
     }
 
-    pub fn run(&self) {
-        let mut helper = Helper::new(env::args(), self.name, self.version, false, self.padding);
+    /// Renders a man page for every command of the application.
+    pub fn man_pages(&self) -> Vec<ManPage> {
+        man::man_pages(&self.spec(), self.version)
+    }
 
-        if env::args().any(|arg| &arg == "--version") {
-            println!("{}", helper.version);
-            exit(0);
+    /// Writes the man pages of the application into `dir`.
+    pub fn generate_man_pages(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        for page in self.man_pages() {
+            fs::write(dir.join(&page.file_name), page.content)?;
         }
-        helper.help = env::args().any(|arg| &arg == "--help");
+        Ok(())
+    }
+
+    fn is_completions(&self, helper: &Helper) -> bool {
+        self.completions && helper.args.peek() == Some(OsStr::new("completions"))
+    }
+
+    /// Prints a completion script, once the name of the command is read,
+    /// and returns the exit status.
+    fn completions(&self, mut helper: Helper, spec: CommandSpec) -> u8 {
+        let token = helper.args.next();
+        if helper.help
+            || token
+                .as_ref()
+                .is_some_and(|token| token == "-h" || token == "--help")
+        {
+            println!(
+                "{}\n\nUSAGE:\n  {} [OPTIONS] <shell>\n\nARGS:\n{}\n\nOPTIONS:\n{}",
+                COMPLETIONS_ABOUT,
+                helper.command(),
+                item_message("<shell>", &Shell::NAMES.join(", "), helper.padding),
+                help_message(helper.padding),
+            );
+            return 0;
+        }
+        let shell = match token {
+            Some(shell) => shell.to_string_lossy().parse(),
+            None => Err(ParseError::missing_argument("shell")),
+        };
+        let shell: Shell = match shell {
+            Ok(shell) => shell,
+            Err(err) => {
+                eprintln!("{}", err.with_command(&helper.command()));
+                return EXIT_STATUS;
+            }
+        };
+        if let Err(err) = completion::generate(shell, &spec, &mut io::stdout()) {
+            eprintln!("{}", err);
+            return 1;
+        }
+        0
+    }
+
+    fn args<I, T>(&self, args: I) -> Result<Args, ParseError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let mut args = Args::new(args);
+        args.set_prefix_matching(self.prefix_matching);
+        #[cfg(feature = "config")]
+        if let Some(path) = &self.config {
+            if let Some(config) = Config::load(path)? {
+                args.set_config(config);
+            }
+        }
+        Ok(args)
+    }
+
+    fn helper<I, T>(&self, args: I) -> Result<Helper<'a>, ParseError>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let args = self.args(args)?;
+        let mut helper = Helper::new(args, self.name, self.version, false, self.padding);
         let _process = helper.args.next();
+        Ok(helper)
+    }
+
+    /// Whether `--version` is given in place of a command or an option.
+    fn is_version(&self, helper: &Helper) -> bool {
+        helper.args.peek() == Some(OsStr::new("--version"))
+    }
+
+    pub fn run(&self) -> ExitCode {
+        self.run_from(env::args_os())
+    }
 
+    /// Runs the application with `args` in place of the process arguments.
+    /// The first token is the program name, as with [`env::args_os`].
+    /// An error is printed and returned as the exit status `128`, without exiting the process.
+    pub fn run_from<I, T>(&self, args: I) -> ExitCode
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let mut helper = match self.helper(args) {
+            Ok(helper) => helper,
+            Err(err) => return err.report(),
+        };
+
+        if self.is_completions(&helper) {
+            helper.args.next();
+            helper.cmds.push("completions".to_string());
+            return ExitCode::from(self.completions(helper, self.spec()));
+        }
         if self.cmds.len() == 1 {
-            let key = self.cmds.keys().next().unwrap();
-            match self.cmds.get(key).unwrap() {
-                Runner::FType(cmd) => cmd(helper),
-                Runner::MType(cmd) => cmd.run(helper),
+            if self.is_version(&helper) {
+                println!("{}", helper.version);
+                return ExitCode::SUCCESS;
             }
+            self.cmds.values().next().unwrap().1.run(helper)
         } else {
-            match helper.args.next() {
-                Some(cmd) => match self.cmds.get(cmd.as_str()) {
-                    Some(runner) => {
-                        helper.cmds.push(cmd);
-                        match runner {
-                            Runner::FType(cmd) => cmd(helper),
-                            Runner::MType(cmd) => cmd.run(helper),
-                        }
-                    }
-                    _ => self.help(helper),
+            let mut names: Vec<&str> = self.cmds.keys().copied().collect();
+            if self.completions {
+                names.push("completions");
+            }
+            match next_command(&mut helper, &names) {
+                Next::Command(name) => match self.cmds.get(name) {
+                    Some((_, runner)) => runner.run(helper),
+                    None => ExitCode::from(self.completions(helper, self.spec())),
                 },
-                _ => self.help(helper),
+                Next::Help => {
+                    self.help(helper);
+                    ExitCode::SUCCESS
+                }
+                Next::Exit(code) => code,
+            }
+        }
+    }
+
+    /// Parses the process arguments into `T` instead of running a registered command.
+    /// `-h`, `--help` and `--version` are handled as in [`Application::run`].
+    pub fn parse<T>(&self) -> T
+    where
+        T: ArgsNew + Help,
+    {
+        self.parse_from(env::args_os())
+    }
+
+    /// Same as [`Application::parse`] with `args` in place of the process arguments.
+    pub fn parse_from<T, I, S>(&self, args: I) -> T
+    where
+        T: ArgsNew + Help,
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        let mut helper = self.helper(args).unwrap_or_else(|err| err.exit());
+        if self.is_completions(&helper) {
+            helper.args.next();
+            helper.cmds.push("completions".to_string());
+            let mut spec = T::spec();
+            spec.name = self.name.to_string();
+            spec.commands.push(completions_spec());
+            exit(self.completions(helper, spec).into());
+        }
+        if self.is_version(&helper) {
+            println!("{}", helper.version);
+            exit(0);
+        }
+        let command = helper.command();
+        match T::from_args(helper.args.clone()) {
+            Ok(value) => value,
+            Err(err) if err.kind() == ErrorKind::HelpRequested => {
+                helper.verbose = err.token() == Some("--help");
+                T::help(helper);
+                exit(0);
             }
+            Err(err) => err.with_command(&command).exit(),
         }
     }
+
+    /// Parses `args` into `T` and returns the error instead of exiting.
+    /// The first token is the program name, as with [`env::args_os`].
+    /// `-h` and `--help` are returned as an error of kind [`ErrorKind::HelpRequested`].
+    ///
+    /// ```
+    /// use eclip::{Application, Command, ErrorKind};
+    ///
+    /// #[derive(Command, Debug)]
+    /// struct Cli {
+    ///     #[option(short = "q")]
+    ///     quiet: bool,
+    /// }
+    ///
+    /// let app = Application::new("app");
+    /// let cli: Cli = app.try_parse_from(["app", "-q"]).unwrap();
+    /// assert!(cli.quiet);
+    ///
+    /// let err = app.try_parse_from::<Cli, _, _>(["app", "--help"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::HelpRequested);
+    /// ```
+    pub fn try_parse_from<T, I, S>(&self, args: I) -> Result<T, ParseError>
+    where
+        T: ArgsNew,
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        let mut args = self.args(args)?;
+        let _process = args.next();
+        T::from_args(args).map_err(|err| err.with_command(self.name))
+    }
+}
+
+const COMPLETIONS_ABOUT: &str = "Print a shell completion script.";
+
+fn completions_spec() -> CommandSpec {
+    let mut spec = CommandSpec::new("completions");
+    spec.about = Some(COMPLETIONS_ABOUT.to_string());
+    spec.arguments.push(ArgumentSpec {
+        name: "shell".to_string(),
+        required: true,
+        hint: ValueHint::Choices(Shell::NAMES),
+        ..Default::default()
+    });
+    spec
 }
 
 pub struct SubCommand<'a> {
-    cmds: BTreeMap<&'a str, Runner<'a>>,
+    cmds: BTreeMap<&'a str, (&'a str, Runner<'a>)>,
 }
 
 impl<'a> SubCommand<'a> {
@@ -169,45 +588,80 @@ impl<'a> SubCommand<'a> {
 
     fn help(&self, helper: Helper) {
         println!(
-            "USAGE:\n  {} COMMAND [OPTIONS] [ARGS]...\n\nOPTIONS:\n{}\n\nCOMMANDS:",
-            helper.name,
+            "USAGE:\n  {} COMMAND [OPTIONS] [ARGS]...\n\nOPTIONS:\n{}\n\nCOMMANDS:\n{}",
+            helper.command(),
             help_message(helper.padding),
+            commands_message(
+                self.cmds.iter().map(|(name, (about, _))| (*name, *about)),
+                helper.padding
+            ),
         );
-        for cmd in self.cmds.keys() {
-            println!("  {}", cmd)
-        }
     }
 
-    pub fn add_command(mut self, name: &'a str, cmd: fn(Helper)) -> Self {
-        self.cmds.insert(name, Runner::FType(cmd));
+    pub fn add_command(self, name: &'a str, cmd: fn(Helper) -> ExitCode) -> Self {
+        self.add_command_with_about(name, "", cmd)
+    }
+
+    pub fn add_command_with_about(
+        mut self,
+        name: &'a str,
+        about: &'a str,
+        cmd: fn(Helper) -> ExitCode,
+    ) -> Self {
+        self.cmds
+            .insert(name, (about, Runner::FType(cmd, CommandSpec::default)));
         self
     }
 
-    pub fn add_subcommand(mut self, name: &'a str, subcmd: SubCommand<'a>) -> Self {
-        self.cmds.insert(name, Runner::MType(subcmd));
+    /// Adds a derived command, so that its description and options are known to
+    /// the help and shell completions.
+    pub fn register<T>(mut self, name: &'a str) -> Self
+    where
+        T: Command + ArgsNew + Help,
+    {
+        let about = T::about().unwrap_or_default();
+        self.cmds
+            .insert(name, (about, Runner::FType(T::entry_point, T::spec)));
         self
     }
 
-    fn run(&self, mut helper: Helper) {
+    pub fn add_subcommand(self, name: &'a str, subcmd: SubCommand<'a>) -> Self {
+        self.add_subcommand_with_about(name, "", subcmd)
+    }
+
+    pub fn add_subcommand_with_about(
+        mut self,
+        name: &'a str,
+        about: &'a str,
+        subcmd: SubCommand<'a>,
+    ) -> Self {
+        self.cmds.insert(name, (about, Runner::MType(subcmd)));
+        self
+    }
+
+    fn spec(&self) -> CommandSpec {
+        CommandSpec {
+            commands: self
+                .cmds
+                .iter()
+                .map(|(name, (about, runner))| runner.spec(name, about))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn run(&self, mut helper: Helper) -> ExitCode {
         if self.cmds.len() == 1 {
-            let key = self.cmds.keys().next().unwrap();
-            match self.cmds.get(key).unwrap() {
-                Runner::FType(cmd) => cmd(helper),
-                Runner::MType(cmd) => cmd.run(helper),
-            }
+            self.cmds.values().next().unwrap().1.run(helper)
         } else {
-            match helper.args.next() {
-                Some(cmd) => match self.cmds.get(cmd.as_str()) {
-                    Some(runner) => {
-                        helper.cmds.push(cmd);
-                        match runner {
-                            Runner::FType(cmd) => cmd(helper),
-                            Runner::MType(cmd) => cmd.run(helper),
-                        }
-                    }
-                    _ => self.help(helper),
-                },
-                _ => self.help(helper),
+            let names: Vec<&str> = self.cmds.keys().copied().collect();
+            match next_command(&mut helper, &names) {
+                Next::Command(name) => self.cmds[name].1.run(helper),
+                Next::Help => {
+                    self.help(helper);
+                    ExitCode::SUCCESS
+                }
+                Next::Exit(code) => code,
             }
         }
     }
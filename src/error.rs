@@ -0,0 +1,374 @@
+use std::fmt;
+use std::process::{exit, ExitCode};
+
+use crate::utils::suggest;
+
+/// The exit status of a process which failed to parse its arguments.
+pub(crate) const EXIT_STATUS: u8 = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An option that takes a value was given without one.
+    MissingValue,
+    /// A value could not be converted into the type of its field.
+    InvalidValue,
+    /// A positional argument was not given.
+    MissingArgument,
+    /// A token is not valid UTF-8.
+    InvalidUtf8,
+    /// A command name is not known.
+    UnknownCommand,
+    /// A command name is a prefix of more than one command.
+    AmbiguousCommand,
+    /// A command name was not given.
+    MissingCommand,
+    /// A config file could not be read.
+    InvalidConfig,
+    /// An option is not known.
+    UnknownOption,
+    /// A positional argument was given after all of them were filled.
+    UnexpectedArgument,
+    /// A value was attached to an option which takes none, e.g. `--quiet=yes`.
+    UnexpectedValue,
+    /// An argument or option was given too few or too many values.
+    WrongValueCount,
+    /// Two options were given which cannot be used together.
+    ArgumentConflict,
+    /// An option was given without another one which it requires.
+    MissingRequirement,
+    /// `-h` or `--help` was given, which `Application` answers by showing the help.
+    HelpRequested,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ErrorKind,
+    context: Box<Context>,
+}
+
+/// The details of an error, boxed to keep `Result<_, ParseError>` small.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Context {
+    token: Option<String>,
+    field: Option<String>,
+    expected: Option<String>,
+    origin: Option<String>,
+    message: Option<String>,
+    missing: Vec<String>,
+    usage: Option<String>,
+    suggestion: Option<String>,
+}
+
+impl ParseError {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            context: Box::default(),
+        }
+    }
+
+    pub fn missing_value(token: &str) -> Self {
+        Self::new(ErrorKind::MissingValue).with_token(token)
+    }
+
+    /// An option which takes `count` values per occurrence, e.g. `--size W H`, was given fewer.
+    pub fn missing_values(token: &str, count: usize) -> Self {
+        Self::missing_value(token).with_expected(&count.to_string())
+    }
+
+    pub fn invalid_value(token: &str, expected: &str) -> Self {
+        Self::new(ErrorKind::InvalidValue)
+            .with_token(token)
+            .with_expected(expected)
+    }
+
+    /// A value which was converted, but rejected with `message`, e.g. by `validate_with`.
+    pub fn rejected_value(message: &str) -> Self {
+        Self::new(ErrorKind::InvalidValue).with_message(message)
+    }
+
+    pub fn missing_argument(field: &str) -> Self {
+        Self::new(ErrorKind::MissingArgument).with_field(field)
+    }
+
+    /// Reports every missing item at once, e.g. `["<path>", "--name"]`.
+    pub fn missing_arguments(items: &[&str]) -> Self {
+        let mut err = Self::new(ErrorKind::MissingArgument);
+        err.context.missing = items.iter().map(|item| item.to_string()).collect();
+        err
+    }
+
+    pub fn invalid_utf8(token: &str) -> Self {
+        Self::new(ErrorKind::InvalidUtf8).with_token(token)
+    }
+
+    /// Suggests the closest of `commands` when `token` looks like a typo of one.
+    pub fn unknown_command(token: &str, commands: &[&str]) -> Self {
+        let mut err = Self::new(ErrorKind::UnknownCommand).with_token(token);
+        err.context.suggestion = suggest(token, commands.iter().copied()).map(String::from);
+        err
+    }
+
+    pub fn ambiguous_command(token: &str, commands: &[&str]) -> Self {
+        let mut err = Self::new(ErrorKind::AmbiguousCommand).with_token(token);
+        err.context.message = Some(commands.join(", "));
+        err
+    }
+
+    pub fn missing_command() -> Self {
+        Self::new(ErrorKind::MissingCommand)
+    }
+
+    pub fn invalid_config(path: &str, message: &str) -> Self {
+        let mut err = Self::new(ErrorKind::InvalidConfig).with_token(path);
+        err.context.message = Some(message.to_string());
+        err
+    }
+
+    /// Suggests the closest of `keys` when `token` looks like a typo of one.
+    pub fn unknown_option(token: &str, keys: &[&str]) -> Self {
+        let mut err = Self::new(ErrorKind::UnknownOption).with_token(token);
+        err.context.suggestion = suggest(token, keys.iter().copied()).map(String::from);
+        err
+    }
+
+    pub fn unexpected_argument(token: &str) -> Self {
+        Self::new(ErrorKind::UnexpectedArgument).with_token(token)
+    }
+
+    pub fn unexpected_value(option: &str, value: &str) -> Self {
+        Self::new(ErrorKind::UnexpectedValue)
+            .with_token(value)
+            .with_field(option)
+    }
+
+    /// `expected` describes the accepted number, e.g. `at least 2` or `1 to 3`.
+    pub fn value_count(field: &str, expected: &str, given: usize) -> Self {
+        let mut err = Self::new(ErrorKind::WrongValueCount)
+            .with_field(field)
+            .with_expected(expected);
+        err.context.message = Some(given.to_string());
+        err
+    }
+
+    /// `option` was given together with `other`, e.g. `--json` with `--table`.
+    pub fn argument_conflict(option: &str, other: &str) -> Self {
+        let mut err = Self::new(ErrorKind::ArgumentConflict).with_field(option);
+        err.context.message = Some(other.to_string());
+        err
+    }
+
+    /// `option` was given without `required`, e.g. `--user` without `--password`.
+    pub fn missing_requirement(option: &str, required: &str) -> Self {
+        let mut err = Self::new(ErrorKind::MissingRequirement).with_field(option);
+        err.context.message = Some(required.to_string());
+        err
+    }
+
+    /// `token` is `-h` or `--help`, which were given where a command was parsed.
+    pub fn help_requested(token: &str) -> Self {
+        Self::new(ErrorKind::HelpRequested).with_token(token)
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.context.token = Some(token.to_string());
+        self
+    }
+
+    /// Sets the field name unless a more specific one is already known.
+    pub fn with_field(mut self, field: &str) -> Self {
+        if self.context.field.is_none() {
+            self.context.field = Some(field.to_string());
+        }
+        self
+    }
+
+    pub fn with_expected(mut self, expected: &str) -> Self {
+        self.context.expected = Some(expected.to_string());
+        self
+    }
+
+    /// Sets the reason of an invalid value, e.g. the error of `FromStr::from_str`.
+    pub fn with_message(mut self, message: &str) -> Self {
+        self.context.message = Some(message.to_string());
+        self
+    }
+
+    /// Sets where a value came from when it was not given on the command line,
+    /// e.g. `environment variable TOKEN`.
+    pub fn with_origin(mut self, origin: &str) -> Self {
+        self.context.origin = Some(origin.to_string());
+        self
+    }
+
+    /// Sets the usage of the command, e.g. `[OPTIONS] <path>`, shown below the error.
+    pub fn with_usage(mut self, usage: &str) -> Self {
+        self.context.usage = Some(usage.to_string());
+        self
+    }
+
+    /// Prefixes the usage with the name of the command which was being parsed.
+    pub fn with_command(mut self, command: &str) -> Self {
+        if let Some(usage) = &mut self.context.usage {
+            *usage = format!("{} {}", command, usage);
+        }
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.context.token.as_deref()
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.context.field.as_deref()
+    }
+
+    pub fn expected(&self) -> Option<&str> {
+        self.context.expected.as_deref()
+    }
+
+    pub fn origin(&self) -> Option<&str> {
+        self.context.origin.as_deref()
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.context.message.as_deref()
+    }
+
+    pub fn missing(&self) -> &[String] {
+        &self.context.missing
+    }
+
+    pub fn usage(&self) -> Option<&str> {
+        self.context.usage.as_deref()
+    }
+
+    pub fn suggestion(&self) -> Option<&str> {
+        self.context.suggestion.as_deref()
+    }
+
+    /// Prints the error to stderr and exits the process.
+    pub fn exit(&self) -> ! {
+        eprintln!("{}", self);
+        exit(EXIT_STATUS.into());
+    }
+
+    /// Prints the error to stderr and returns the exit status, as `exit` would exit with.
+    pub fn report(&self) -> ExitCode {
+        eprintln!("{}", self);
+        ExitCode::from(EXIT_STATUS)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let token = self.context.token.as_deref().unwrap_or_default();
+        match self.kind {
+            ErrorKind::MissingValue => match &self.context.expected {
+                Some(count) => write!(f, "\"{}\" requires {} arguments", token, count),
+                None => write!(f, "\"{}\" requires one argument", token),
+            },
+            ErrorKind::InvalidValue => {
+                write!(f, "Invalid value")?;
+                if let Some(token) = &self.context.token {
+                    write!(f, " \"{}\"", token)?;
+                }
+                if let Some(field) = &self.context.field {
+                    write!(f, " for <{}>", field)?;
+                }
+                if let Some(origin) = &self.context.origin {
+                    write!(f, " from {}", origin)?;
+                }
+                match (&self.context.expected, &self.context.message) {
+                    (Some(expected), Some(message)) => {
+                        write!(f, ": expected {} ({})", expected, message)
+                    }
+                    (Some(expected), None) => write!(f, ": expected {}", expected),
+                    (None, Some(message)) => write!(f, ": {}", message),
+                    (None, None) => Ok(()),
+                }
+            }
+            ErrorKind::MissingArgument => {
+                match (self.context.missing.as_slice(), &self.context.field) {
+                    ([item], _) => write!(f, "Missing argument {}", item),
+                    ([], Some(field)) => write!(f, "Missing argument <{}>", field),
+                    ([], None) => write!(f, "More arguments are needed."),
+                    (items, _) => write!(f, "Missing arguments {}", items.join(", ")),
+                }
+            }
+            ErrorKind::InvalidUtf8 => write!(f, "Invalid UTF-8 in \"{}\"", token),
+            ErrorKind::UnknownCommand => {
+                write!(f, "Unknown command \"{}\"", token)?;
+                if let Some(suggestion) = &self.context.suggestion {
+                    write!(f, ", did you mean \"{}\"?", suggestion)?;
+                }
+                Ok(())
+            }
+            ErrorKind::AmbiguousCommand => {
+                write!(f, "Ambiguous command \"{}\"", token)?;
+                if let Some(message) = &self.context.message {
+                    write!(f, ", which may be {}", message)?;
+                }
+                Ok(())
+            }
+            ErrorKind::MissingCommand => write!(f, "A command is needed."),
+            ErrorKind::InvalidConfig => {
+                match &self.context.origin {
+                    Some(origin) => write!(f, "Invalid config at {}", origin)?,
+                    None => write!(f, "Invalid config file \"{}\"", token)?,
+                }
+                if let Some(message) = &self.context.message {
+                    write!(f, ": {}", message.trim_end())?;
+                }
+                Ok(())
+            }
+            ErrorKind::UnknownOption => {
+                write!(f, "Unknown option \"{}\"", token)?;
+                if let Some(suggestion) = &self.context.suggestion {
+                    write!(f, ", did you mean \"{}\"?", suggestion)?;
+                }
+                Ok(())
+            }
+            ErrorKind::UnexpectedArgument => write!(f, "Unexpected argument \"{}\"", token),
+            ErrorKind::WrongValueCount => {
+                let field = self.context.field.as_deref().unwrap_or_default();
+                let expected = self.context.expected.as_deref().unwrap_or_default();
+                let given = self.context.message.as_deref().unwrap_or_default();
+                write!(
+                    f,
+                    "<{}> takes {} values, but got {}",
+                    field, expected, given
+                )
+            }
+            ErrorKind::UnexpectedValue => {
+                let field = self.context.field.as_deref().unwrap_or_default();
+                write!(
+                    f,
+                    "\"{}\" does not take a value, but \"{}\" was given",
+                    field, token
+                )
+            }
+            ErrorKind::ArgumentConflict => {
+                let field = self.context.field.as_deref().unwrap_or_default();
+                let other = self.context.message.as_deref().unwrap_or_default();
+                write!(f, "\"{}\" cannot be used with \"{}\"", field, other)
+            }
+            ErrorKind::MissingRequirement => {
+                let field = self.context.field.as_deref().unwrap_or_default();
+                let required = self.context.message.as_deref().unwrap_or_default();
+                write!(f, "\"{}\" requires \"{}\"", field, required)
+            }
+            ErrorKind::HelpRequested => write!(f, "Help was requested with \"{}\"", token),
+        }?;
+        if let Some(usage) = &self.context.usage {
+            write!(f, "\n\nUSAGE:\n  {}", usage)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
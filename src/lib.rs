@@ -14,6 +14,8 @@
 //! }
 //!
 //! impl Command for Command1 {
+//!     type Output = ();
+//!
 //!     fn run(&self) {
 //!         println!("Run Command 1: {:?}", self)
 //!     }
@@ -23,6 +25,8 @@
 //! struct Command2 {}
 //!
 //! impl Command for Command2 {
+//!     type Output = ();
+//!
 //!     fn run(&self) {
 //!         println!("Run Command 2")
 //!     }
@@ -32,6 +36,8 @@
 //! struct Command3;
 //!
 //! impl Command for Command3 {
+//!     type Output = ();
+//!
 //!     fn run(&self) {
 //!         println!("Run Command 3")
 //!     }
@@ -41,6 +47,8 @@
 //! struct Command4 ();
 //!
 //! impl Command for Command4 {
+//!     type Output = ();
+//!
 //!     fn run(&self) {
 //!         println!("Run Command 4")
 //!     }
@@ -58,12 +66,32 @@
 //!     .run();
 //! ```
 
-pub use eclip_derive::Command;
+pub use eclip_derive::{Command, ValueEnum};
 
+mod args;
+mod completion;
+mod config;
+mod constraint;
 mod core;
+mod error;
+mod man;
+mod spec;
 mod utils;
 mod validator;
+#[cfg(feature = "log")]
+mod verbosity;
 
+pub use crate::args::Args;
+pub use crate::completion::Shell;
+pub use crate::config::ConfigValue;
+pub use crate::constraint::{check_len, check_possible_values, check_range, check_with};
+#[cfg(feature = "regex")]
+pub use crate::constraint::{check_regex, Pattern};
 pub use crate::core::{Application, ArgsNew, Command, Help, Helper, SubCommand};
-pub use crate::utils::help_message;
-pub use crate::validator::{ArgValue, Validator};
+pub use crate::error::{ErrorKind, ParseError};
+pub use crate::man::{man_pages, ManPage};
+pub use crate::spec::{ArgumentSpec, CommandSpec, OptionSpec, ValueHint};
+pub use crate::utils::{commands_message, help_message, item_message};
+pub use crate::validator::{parse_with, split_with, ArgValue, Validator};
+#[cfg(feature = "log")]
+pub use crate::verbosity::Verbosity;
@@ -0,0 +1,153 @@
+use crate::spec::{flatten, options, ArgumentSpec, CommandSpec};
+
+/// A man page of one command in roff format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManPage {
+    /// The file name, e.g. `app-remote-add.1`.
+    pub file_name: String,
+    pub content: String,
+}
+
+/// Renders a man page for every command in the tree, root first.
+pub fn man_pages(spec: &CommandSpec, version: &str) -> Vec<ManPage> {
+    let mut list = Vec::new();
+    flatten(spec, vec![spec.name.as_str()], &mut list);
+    list.iter()
+        .map(|(path, cmd)| ManPage {
+            file_name: format!("{}.1", path.join("-")),
+            content: render(path, cmd, version),
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let line = line.replace('\\', "\\e").replace('-', "\\-");
+            if line.starts_with('.') || line.starts_with('\'') {
+                format!("\\&{}", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Separates paragraphs by `.PP`, as blank lines are not portable in roff.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(|paragraph| escape(paragraph.trim()))
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n.PP\n")
+}
+
+/// Shows an argument as `<name>`, `[name]`, `<name>...` or `[name]...`.
+fn usage(arg: &ArgumentSpec) -> String {
+    let name = format!("\\fI{}\\fR", escape(&arg.name));
+    let name = if arg.required {
+        format!("<{}>", name)
+    } else {
+        format!("[{}]", name)
+    };
+    if arg.multiple {
+        format!("{}...", name)
+    } else {
+        name
+    }
+}
+
+fn render(path: &[&str], spec: &CommandSpec, version: &str) -> String {
+    let root = path.len() == 1;
+    let mut page = format!(
+        ".TH {} 1 \"\" \"{}\"\n",
+        escape(&path.join("-").to_uppercase()),
+        escape(format!("{} {}", path[0], version).trim()),
+    );
+
+    page.push_str(".SH NAME\n");
+    page.push_str(&escape(&path.join("-")));
+    if let Some(about) = &spec.about {
+        page.push_str(&format!(" \\- {}", escape(about)));
+    }
+    page.push('\n');
+
+    page.push_str(".SH SYNOPSIS\n");
+    page.push_str(&format!(
+        "\\fB{}\\fR [\\fIOPTIONS\\fR]",
+        escape(&path.join(" "))
+    ));
+    for opt in spec.options.iter().filter(|opt| opt.required) {
+        page.push_str(&format!(" \\fB{}\\fR", escape(&opt.keys().pop().unwrap())));
+        if let Some(value) = &opt.value {
+            page.push_str(&format!(" \\fI{}\\fR", escape(value)));
+        }
+    }
+    for arg in &spec.arguments {
+        page.push_str(&format!(" {}", usage(arg)));
+    }
+    if !spec.commands.is_empty() {
+        page.push_str(" \\fICOMMAND\\fR");
+    }
+    page.push('\n');
+
+    if let Some(description) = spec.description() {
+        page.push_str(".SH DESCRIPTION\n");
+        page.push_str(&paragraphs(description));
+        page.push('\n');
+    }
+
+    page.push_str(".SH OPTIONS\n");
+    for opt in options(spec, root) {
+        let keys = opt
+            .keys()
+            .iter()
+            .map(|key| format!("\\fB{}\\fR", escape(key)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        page.push_str(&format!(".TP\n{}", keys));
+        if let Some(value) = &opt.value {
+            page.push_str(&format!(" \\fI{}\\fR", escape(value)));
+        }
+        page.push('\n');
+        if let Some(description) = opt.description() {
+            page.push_str(&paragraphs(description));
+            page.push('\n');
+        }
+        if let Some(env) = &opt.env {
+            page.push_str(&format!(".br\n[env: {}]\n", escape(env)));
+        }
+        if let Some(default) = &opt.default {
+            page.push_str(&format!(".br\n[default: {}]\n", escape(default)));
+        }
+    }
+
+    if !spec.arguments.is_empty() {
+        page.push_str(".SH ARGUMENTS\n");
+        for arg in &spec.arguments {
+            page.push_str(&format!(".TP\n{}\n", usage(arg)));
+            if let Some(description) = arg.description() {
+                page.push_str(&paragraphs(description));
+                page.push('\n');
+            }
+        }
+    }
+
+    if !spec.commands.is_empty() {
+        page.push_str(".SH COMMANDS\n");
+        for cmd in &spec.commands {
+            let name = format!("{}-{}", path.join("-"), cmd.name);
+            page.push_str(&format!(".TP\n\\fB{}\\fR(1)\n", escape(&name)));
+            if let Some(about) = &cmd.about {
+                page.push_str(&escape(about));
+                page.push('\n');
+            }
+        }
+    }
+
+    if !version.is_empty() {
+        page.push_str(&format!(".SH VERSION\n{}\n", escape(version)));
+    }
+    page
+}
@@ -0,0 +1,137 @@
+/// What kind of value an option or argument takes, used to complete it in a shell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueHint {
+    #[default]
+    Unknown,
+    Path,
+    Number,
+    Choices(&'static [&'static str]),
+}
+
+impl ValueHint {
+    /// Shows a value named `name` in help, e.g. `<FORMAT>`, or `<FORMAT: json|yaml>` for choices.
+    pub fn placeholder(&self, name: &str) -> String {
+        match self {
+            ValueHint::Choices(choices) => format!("<{}: {}>", name, choices.join("|")),
+            _ => format!("<{}>", name),
+        }
+    }
+}
+
+/// A description of a command and everything it accepts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub name: String,
+    pub about: Option<String>,
+    pub long_about: Option<String>,
+    pub options: Vec<OptionSpec>,
+    pub arguments: Vec<ArgumentSpec>,
+    pub commands: Vec<CommandSpec>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptionSpec {
+    /// The short key without `-`.
+    pub short: Option<String>,
+    /// The long key without `--`.
+    pub long: Option<String>,
+    /// The name of the value, or `None` for a flag.
+    pub value: Option<String>,
+    pub default: Option<String>,
+    /// The environment variable read when the option is not given.
+    pub env: Option<String>,
+    pub required: bool,
+    /// Whether `--no-<long>` turns the flag off.
+    pub negatable: bool,
+    pub hint: ValueHint,
+    pub help: Option<String>,
+    pub long_help: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArgumentSpec {
+    pub name: String,
+    /// Whether the argument must be given, unlike an `Option` or an empty `Vec`.
+    pub required: bool,
+    /// Whether the argument takes more than one value.
+    pub multiple: bool,
+    pub hint: ValueHint,
+    pub help: Option<String>,
+    pub long_help: Option<String>,
+}
+
+impl CommandSpec {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The description of the command, preferring the long one.
+    pub fn description(&self) -> Option<&str> {
+        self.long_about.as_deref().or(self.about.as_deref())
+    }
+}
+
+impl OptionSpec {
+    /// The keys of the option, e.g. `["-q", "--quiet"]`, or `["--color", "--no-color"]`.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        if let Some(short) = &self.short {
+            keys.push(format!("-{}", short));
+        }
+        if let Some(long) = &self.long {
+            keys.push(format!("--{}", long));
+            if self.negatable {
+                keys.push(format!("--no-{}", long));
+            }
+        }
+        keys
+    }
+
+    /// The description of the option, preferring the long one.
+    pub fn description(&self) -> Option<&str> {
+        self.long_help.as_deref().or(self.help.as_deref())
+    }
+}
+
+impl ArgumentSpec {
+    /// The description of the argument, preferring the long one.
+    pub fn description(&self) -> Option<&str> {
+        self.long_help.as_deref().or(self.help.as_deref())
+    }
+}
+
+/// Lists every command in the tree with the names leading to it, root first.
+pub(crate) fn flatten<'a>(
+    spec: &'a CommandSpec,
+    path: Vec<&'a str>,
+    list: &mut Vec<(Vec<&'a str>, &'a CommandSpec)>,
+) {
+    list.push((path.clone(), spec));
+    for cmd in &spec.commands {
+        let mut path = path.clone();
+        path.push(&cmd.name);
+        flatten(cmd, path, list);
+    }
+}
+
+/// The options of a command including `-h`, `--help` and, for the root, `--version`.
+pub(crate) fn options(spec: &CommandSpec, root: bool) -> Vec<OptionSpec> {
+    let mut options = spec.options.clone();
+    options.push(OptionSpec {
+        short: Some("h".to_string()),
+        long: Some("help".to_string()),
+        help: Some("Show this message.".to_string()),
+        ..Default::default()
+    });
+    if root {
+        options.push(OptionSpec {
+            long: Some("version".to_string()),
+            help: Some("Show this version.".to_string()),
+            ..Default::default()
+        });
+    }
+    options
+}
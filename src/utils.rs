@@ -1,6 +1,71 @@
 pub fn help_message(padding: usize) -> String {
     format!(
         "  {:<padding$} {}\n  {:<padding$} {}",
-        "--help", "Show this message.", "--version", "Show this version.",
+        "-h, --help", "Show this message.", "--version", "Show this version.",
     )
 }
+
+/// Formats one row of a help table, aligning every line of `help` at `padding`.
+pub fn item_message(name: &str, help: &str, padding: usize) -> String {
+    let mut lines = help.lines();
+    let mut message = match lines.next() {
+        None => return format!("  {}", name),
+        Some(line) if name.len() >= padding => {
+            format!("  {}\n  {:padding$} {}", name, "", line)
+        }
+        Some(line) => format!("  {:<padding$} {}", name, line),
+    };
+    for line in lines {
+        if line.is_empty() {
+            message.push('\n');
+        } else {
+            message.push_str(&format!("\n  {:padding$} {}", "", line));
+        }
+    }
+    message
+}
+
+pub fn commands_message<'a, I>(cmds: I, padding: usize) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    cmds.into_iter()
+        .map(|(name, about)| item_message(name, about, padding))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The number of single character edits which turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(cur)
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// Picks the candidate closest to a mistyped `token`, if any is close enough.
+pub(crate) fn suggest<'a, I>(token: &str, candidates: I) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(token, candidate), candidate))
+        .filter(|(distance, candidate)| {
+            distance * 3 <= token.chars().count().max(candidate.chars().count())
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
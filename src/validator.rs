@@ -1,87 +1,277 @@
-use std::env::Args;
-use std::process::exit;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::args::Args;
+use crate::error::ParseError;
+use crate::spec::ValueHint;
 
 pub enum ArgValue {
+    /// An option key, whose value is the next token of `Args`.
     Option(String),
-    Argument(String),
+    /// A token given as an argument, or a value from the environment or a config file.
+    Argument(OsString),
 }
 
-pub trait Validator {
-    fn validate(pre: Self, arg: ArgValue, args: &mut Args) -> Self;
+impl ArgValue {
+    /// The value as it was given, read from `args` for an option.
+    pub fn into_os_string(self, args: &mut Args) -> Result<OsString, ParseError> {
+        match self {
+            ArgValue::Option(arg) => args.next().ok_or_else(|| ParseError::missing_value(&arg)),
+            ArgValue::Argument(arg) => Ok(arg),
+        }
+    }
+
+    /// The value as a `String`, which fails when it is not valid UTF-8.
+    pub fn into_string(self, args: &mut Args) -> Result<String, ParseError> {
+        self.into_os_string(args)?
+            .into_string()
+            .map_err(|arg| ParseError::invalid_utf8(&arg.to_string_lossy()))
+    }
+}
+
+/// Converts the values of an option or argument into the type of its field.
+pub trait Validator: Sized {
+    /// Converts `arg`, given `pre`, the value of an earlier occurrence if any.
+    fn validate(pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError>;
+
+    fn value_hint() -> ValueHint {
+        ValueHint::Unknown
+    }
+
+    /// Whether an option of this type reads a value, which decides how `-abc` is split.
+    fn takes_value() -> bool {
+        true
+    }
+}
+
+/// Reads a value and converts it with `parse`, such as `FromStr::from_str`.
+/// On failure the error of `parse` is reported along with the `expected` type.
+pub fn parse_with<T, E, F>(
+    arg: ArgValue,
+    args: &mut Args,
+    expected: &str,
+    parse: F,
+) -> Result<T, ParseError>
+where
+    E: Display,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    let val = arg.into_string(args)?;
+    parse(&val)
+        .map_err(|err| ParseError::invalid_value(&val, expected).with_message(&err.to_string()))
+}
+
+/// Splits a value at `delimiter` and converts each part, as `delimiter = ','` does
+/// for `--tags a,b,c`, adding them to `pre`, the values of earlier occurrences.
+pub fn split_with<T: Validator>(
+    pre: Option<Vec<T>>,
+    arg: ArgValue,
+    args: &mut Args,
+    delimiter: char,
+) -> Result<Vec<T>, ParseError> {
+    let val = arg.into_string(args)?;
+    let mut list = pre.unwrap_or_default();
+    for part in val.split(delimiter) {
+        list.push(T::validate(None, ArgValue::Argument(part.into()), args)?);
+    }
+    Ok(list)
 }
 
 impl Validator for String {
-    fn validate(_pre: Self, arg: ArgValue, args: &mut Args) -> Self {
-        match arg {
-            ArgValue::Option(arg) => match args.next() {
-                Some(val) => val,
-                None => {
-                    eprintln!("\"{}\" requires one argument", arg);
-                    exit(128);
-                }
-            },
-            ArgValue::Argument(arg) => arg,
-        }
+    fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        arg.into_string(args)
+    }
+}
+
+impl Validator for OsString {
+    fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        arg.into_os_string(args)
+    }
+}
+
+impl Validator for PathBuf {
+    fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        arg.into_os_string(args).map(PathBuf::from)
+    }
+
+    fn value_hint() -> ValueHint {
+        ValueHint::Path
     }
 }
 
 impl Validator for bool {
-    fn validate(_pre: Self, arg: ArgValue, _args: &mut Args) -> Self {
-        match arg {
-            ArgValue::Option(_) => true,
-            ArgValue::Argument(arg) => match arg.parse() {
-                Ok(val) => val,
-                Err(_) => {
-                    eprintln!("Invalid a value");
-                    exit(128);
-                }
+    /// A flag is `true` unless a value is attached, as in `--color=false`.
+    fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        let arg = match arg {
+            ArgValue::Option(_) => match args.take_attached() {
+                Some(value) => ArgValue::Argument(value),
+                None => return Ok(true),
             },
-        }
+            arg => arg,
+        };
+        parse_with(arg, args, "bool", str::parse)
+    }
+
+    fn value_hint() -> ValueHint {
+        ValueHint::Choices(&["true", "false"])
+    }
+
+    fn takes_value() -> bool {
+        false
     }
 }
 
-macro_rules! validator_for_numeric {
-    ( $( $i:ident ),* ) => {
+macro_rules! validator_for_from_str {
+    ( $hint:expr => $( $i:ident ),* ) => {
         $(
             impl Validator for $i {
-                fn validate(_pre: Self, arg: ArgValue, args: &mut Args) -> Self {
-                    let val = match arg {
-                        ArgValue::Option(arg) => match args.next() {
-                            Some(val) => val,
-                            None => {
-                                eprintln!("\"{}\" requires one argument", arg);
-                                exit(128);
-                            }
-                        },
-                        ArgValue::Argument(arg) => arg
-                    };
-                    match val.parse() {
-                        Ok(val) => val,
-                        Err(_) => {
-                            eprintln!("Invalid a value");
-                            exit(128);
-                        }
-                    }
+                fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+                    parse_with(arg, args, stringify!($i), str::parse)
+                }
+
+                fn value_hint() -> ValueHint {
+                    $hint
                 }
             }
         )*
     };
 }
 
-validator_for_numeric!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+validator_for_from_str!(
+    ValueHint::Number =>
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+    NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize,
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize
+);
+validator_for_from_str!(
+    ValueHint::Unknown =>
+    char, IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6
+);
 
-impl<T: Validator + Default> Validator for Option<T> {
-    fn validate(pre: Self, arg: ArgValue, args: &mut Args) -> Self {
-        match pre {
-            Some(pre) => Some(Validator::validate(pre, arg, args)),
-            None => Some(Validator::validate(Default::default(), arg, args)),
-        }
+/// Reads a duration such as `30s`, `1.5h` or `1h30m`, where a bare number means seconds.
+/// The units are `ns`, `us`, `ms`, `s`, `m`, `h` and `d`.
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    if let Ok(secs) = text.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs).map_err(|err| err.to_string());
+    }
+    if text.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    let is_number = |c: char| c.is_ascii_digit() || c == '.';
+    let mut total = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (number, tail) = rest.split_at(rest.find(|c| !is_number(c)).unwrap_or(rest.len()));
+        let (unit, tail) = tail.split_at(tail.find(is_number).unwrap_or(tail.len()));
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("expected a number before \"{}\"", unit))?;
+        let scale = match unit {
+            "ns" => 1e-9,
+            "us" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            "" => return Err(format!("missing unit after \"{}\"", number)),
+            unit => return Err(format!("unknown unit \"{}\"", unit)),
+        };
+        let duration =
+            Duration::try_from_secs_f64(number * scale).map_err(|err| err.to_string())?;
+        total = total
+            .checked_add(duration)
+            .ok_or_else(|| "duration is too long".to_string())?;
+        rest = tail;
     }
+    Ok(total)
 }
 
-impl<T: Validator + Default> Validator for Vec<T> {
-    fn validate(mut pre: Self, arg: ArgValue, args: &mut Args) -> Self {
-        pre.push(Validator::validate(Default::default(), arg, args));
-        pre
+impl Validator for Duration {
+    fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        parse_with(arg, args, "duration", parse_duration)
+    }
+}
+
+/// Reads the `count` values of a tuple, either the tokens after its option as in `--size 80 24`,
+/// or one value separated by commas as in `--size=80,24` or from the environment.
+fn tuple_values(arg: ArgValue, args: &mut Args, count: usize) -> Result<Vec<ArgValue>, ParseError> {
+    let val = match arg {
+        ArgValue::Option(key) => match args.take_attached() {
+            Some(val) => val,
+            None => {
+                let values: Vec<_> = args.take(count).map(ArgValue::Argument).collect();
+                if values.len() < count {
+                    return Err(ParseError::missing_values(&key, count));
+                }
+                return Ok(values);
+            }
+        },
+        ArgValue::Argument(val) => val,
+    };
+    let val = val
+        .into_string()
+        .map_err(|val| ParseError::invalid_utf8(&val.to_string_lossy()))?;
+    let values: Vec<_> = val
+        .split(',')
+        .map(|part| ArgValue::Argument(part.into()))
+        .collect();
+    if values.len() != count {
+        let expected = format!("{} values separated by commas", count);
+        return Err(ParseError::invalid_value(&val, &expected));
+    }
+    Ok(values)
+}
+
+macro_rules! validator_for_tuple {
+    ( $( ( $( $t:ident ),+ ) ),* ) => {
+        $(
+            impl<$( $t: Validator ),+> Validator for ( $( $t, )+ ) {
+                fn validate(_pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+                    let count = [$( stringify!($t) ),+].len();
+                    let mut values = tuple_values(arg, args, count)?.into_iter();
+                    Ok(( $( $t::validate(None, values.next().unwrap(), args)?, )+ ))
+                }
+            }
+        )*
+    };
+}
+
+validator_for_tuple!((T, U), (T, U, V), (T, U, V, W));
+
+impl<T: Validator> Validator for Option<T> {
+    fn validate(pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        T::validate(pre.flatten(), arg, args).map(Some)
+    }
+
+    fn value_hint() -> ValueHint {
+        T::value_hint()
+    }
+
+    fn takes_value() -> bool {
+        T::takes_value()
+    }
+}
+
+impl<T: Validator> Validator for Vec<T> {
+    fn validate(pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        let mut pre = pre.unwrap_or_default();
+        pre.push(T::validate(None, arg, args)?);
+        Ok(pre)
+    }
+
+    fn value_hint() -> ValueHint {
+        T::value_hint()
+    }
+
+    fn takes_value() -> bool {
+        T::takes_value()
     }
 }
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use log::{Level, LevelFilter};
+
+use crate::args::Args;
+use crate::error::ParseError;
+use crate::spec::ValueHint;
+use crate::validator::{parse_with, ArgValue, Validator};
+
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+/// The level of logging, `Warn` unless raised by each `-v` or `--verbose`
+/// and lowered by each `-q` or `--quiet`.
+///
+/// A field declared with `#[option(verbosity)]` is raised by its own keys,
+/// `-v --verbose` unless `short` or `long` is given, and lowered by `-q --quiet`.
+/// From the environment or a config file it reads a level such as `debug`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Verbosity {
+    /// The number of levels above `Warn`, or below it when negative.
+    offset: i8,
+}
+
+impl Verbosity {
+    pub fn log_level_filter(&self) -> LevelFilter {
+        let warn = LevelFilter::Warn as i8;
+        let max = LEVELS.len() as i8 - 1;
+        LEVELS[warn.saturating_add(self.offset).clamp(0, max) as usize]
+    }
+
+    /// The level of logging, or `None` when it is turned off by `-qq`.
+    pub fn log_level(&self) -> Option<Level> {
+        self.log_level_filter().to_level()
+    }
+}
+
+impl From<LevelFilter> for Verbosity {
+    fn from(level: LevelFilter) -> Self {
+        Self {
+            offset: level as i8 - LevelFilter::Warn as i8,
+        }
+    }
+}
+
+impl Validator for Verbosity {
+    fn validate(pre: Option<Self>, arg: ArgValue, args: &mut Args) -> Result<Self, ParseError> {
+        let Verbosity { offset } = pre.unwrap_or_default();
+        match arg {
+            ArgValue::Option(key) if key == "-q" || key == "--quiet" => Ok(Self {
+                offset: offset.saturating_sub(1),
+            }),
+            ArgValue::Option(_) => Ok(Self {
+                offset: offset.saturating_add(1),
+            }),
+            arg => parse_with(arg, args, "level", LevelFilter::from_str).map(Self::from),
+        }
+    }
+
+    fn value_hint() -> ValueHint {
+        ValueHint::Choices(&["off", "error", "warn", "info", "debug", "trace"])
+    }
+
+    fn takes_value() -> bool {
+        false
+    }
+}
@@ -0,0 +1,29 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Compares `actual` with the file `tests/golden/<name>`.
+/// With `ECLIP_BLESS=1` the file is written instead, to accept a change of the output.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(name);
+    if env::var_os("ECLIP_BLESS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "cannot read {}: {}, run with ECLIP_BLESS=1 to create it",
+            path.display(),
+            err
+        )
+    });
+    assert_eq!(
+        expected, actual,
+        "{} differs, run with ECLIP_BLESS=1 to accept the change",
+        name
+    );
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use eclip::{Application, Command, Shell, SubCommand, ValueEnum};
+
+use common::assert_golden;
+
+#[derive(ValueEnum, Debug, Default)]
+enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Build the project.
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+struct Build {
+    /// Colorize the output.
+    #[option(long, negatable, default = true)]
+    color: bool,
+    /// Output format.
+    #[option(short = "f", long)]
+    format: Format,
+    #[option(short = "o", long)]
+    output: Option<std::path::PathBuf>,
+    #[argument]
+    target: Option<String>,
+}
+
+impl Command for Build {
+    type Output = ();
+
+    fn run(&self) {}
+}
+
+/// Add a remote.
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+struct RemoteAdd {
+    #[option(short = "j", long)]
+    jobs: u32,
+    #[argument]
+    url: String,
+}
+
+impl Command for RemoteAdd {
+    type Output = ();
+
+    fn run(&self) {}
+}
+
+fn application() -> Application<'static> {
+    Application::new("ex")
+        .register::<Build>("build")
+        .add_subcommand_with_about(
+            "remote",
+            "Manage remotes.",
+            SubCommand::new().register::<RemoteAdd>("add"),
+        )
+        .enable_completions()
+}
+
+fn script(shell: Shell) -> String {
+    let mut out = Vec::new();
+    application().generate_completions(shell, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn bash() {
+    assert_golden("completion/ex.bash", &script(Shell::Bash));
+}
+
+#[test]
+fn zsh() {
+    assert_golden("completion/_ex.zsh", &script(Shell::Zsh));
+}
+
+#[test]
+fn fish() {
+    assert_golden("completion/ex.fish", &script(Shell::Fish));
+}
+
+#[test]
+fn powershell() {
+    assert_golden("completion/ex.ps1", &script(Shell::PowerShell));
+}
+
+#[test]
+fn offers_choices_and_negated_flags_in_every_shell() {
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+        let script = script(shell);
+        assert!(script.contains("no-color"), "{:?} lacks --no-color", shell);
+        assert!(script.contains("remote"), "{:?} lacks subcommands", shell);
+        assert!(script.contains("json"), "{:?} lacks the choices", shell);
+    }
+}
@@ -0,0 +1,41 @@
+_ex() {
+    local cur prev cmd i opts
+    COMPREPLY=()
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    cmd="ex"
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${cmd},${COMP_WORDS[i]}" in
+            "ex,build") cmd="ex__build" ;;
+            "ex,remote") cmd="ex__remote" ;;
+            "ex,completions") cmd="ex__completions" ;;
+            "ex__remote,add") cmd="ex__remote__add" ;;
+        esac
+    done
+    case "${cmd}" in
+        ex)
+            opts="-h --help --version build remote completions"
+            ;;
+        ex__build)
+            opts="--color --no-color -f --format -o --output -h --help"
+            case "${prev}" in
+                -f|--format) COMPREPLY=($(compgen -W "text json" -- "${cur}")); return 0 ;;
+                -o|--output) COMPREPLY=($(compgen -f -- "${cur}")); return 0 ;;
+            esac
+            ;;
+        ex__remote)
+            opts="-h --help add"
+            ;;
+        ex__remote__add)
+            opts="-j --jobs -h --help"
+            case "${prev}" in
+                -j|--jobs) COMPREPLY=(); return 0 ;;
+            esac
+            ;;
+        ex__completions)
+            opts="-h --help bash zsh fish powershell"
+            ;;
+    esac
+    COMPREPLY=($(compgen -W "${opts}" -- "${cur}"))
+}
+complete -F _ex -o bashdefault -o default ex
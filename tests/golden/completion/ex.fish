@@ -0,0 +1,16 @@
+complete -c ex -n '__fish_use_subcommand' -s h -l help -d 'Show this message.'
+complete -c ex -n '__fish_use_subcommand' -l version -d 'Show this version.'
+complete -c ex -n '__fish_use_subcommand' -f -a 'build' -d 'Build the project.'
+complete -c ex -n '__fish_use_subcommand' -f -a 'remote' -d 'Manage remotes.'
+complete -c ex -n '__fish_use_subcommand' -f -a 'completions' -d 'Print a shell completion script.'
+complete -c ex -n '__fish_seen_subcommand_from build' -l no-color
+complete -c ex -n '__fish_seen_subcommand_from build' -l color -d 'Colorize the output.'
+complete -c ex -n '__fish_seen_subcommand_from build' -s f -l format -d 'Output format.' -x -a 'text json'
+complete -c ex -n '__fish_seen_subcommand_from build' -s o -l output -r -F
+complete -c ex -n '__fish_seen_subcommand_from build' -s h -l help -d 'Show this message.'
+complete -c ex -n '__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add' -s h -l help -d 'Show this message.'
+complete -c ex -n '__fish_seen_subcommand_from remote; and not __fish_seen_subcommand_from add' -f -a 'add' -d 'Add a remote.'
+complete -c ex -n '__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add' -s j -l jobs -x
+complete -c ex -n '__fish_seen_subcommand_from remote; and __fish_seen_subcommand_from add' -s h -l help -d 'Show this message.'
+complete -c ex -n '__fish_seen_subcommand_from completions' -s h -l help -d 'Show this message.'
+complete -c ex -n '__fish_seen_subcommand_from completions' -f -a 'bash zsh fish powershell'
@@ -0,0 +1,72 @@
+Register-ArgumentCompleter -Native -CommandName 'ex' -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+
+    $commandElements = $commandAst.CommandElements
+    $command = @(
+        'ex'
+        for ($i = 1; $i -lt $commandElements.Count; $i++) {
+            $element = $commandElements[$i]
+            if ($element -isnot [System.Management.Automation.Language.StringConstantExpressionAst] -or
+                $element.StringConstantType -ne [System.Management.Automation.Language.StringConstantType]::BareWord -or
+                $element.Value.StartsWith('-') -or
+                $element.Value -eq $wordToComplete) {
+                break
+            }
+            $element.Value
+        }) -join ';'
+    $previous = "$(@($commandElements | Where-Object { $_.Extent.EndOffset -lt $cursorPosition })[-1])"
+
+    switch ("$command;$previous") {
+        { $_ -in 'ex;build;-f', 'ex;build;--format' } { return @('text', 'json').Where{ $_ -like "$wordToComplete*" }.ForEach{ [System.Management.Automation.CompletionResult]::new($_, $_, [System.Management.Automation.CompletionResultType]::ParameterValue, $_) } }
+        { $_ -in 'ex;build;-o', 'ex;build;--output' } { return }
+        { $_ -in 'ex;remote;add;-j', 'ex;remote;add;--jobs' } { return }
+    }
+
+    $completions = @(switch ($command) {
+        'ex' {
+            [System.Management.Automation.CompletionResult]::new('-h', 'h', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--help', 'help', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--version', 'version', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this version.')
+            [System.Management.Automation.CompletionResult]::new('build', 'build', [System.Management.Automation.CompletionResultType]::ParameterValue, 'Build the project.')
+            [System.Management.Automation.CompletionResult]::new('remote', 'remote', [System.Management.Automation.CompletionResultType]::ParameterValue, 'Manage remotes.')
+            [System.Management.Automation.CompletionResult]::new('completions', 'completions', [System.Management.Automation.CompletionResultType]::ParameterValue, 'Print a shell completion script.')
+            break
+        }
+        'ex;build' {
+            [System.Management.Automation.CompletionResult]::new('--color', 'color', [System.Management.Automation.CompletionResultType]::ParameterName, 'Colorize the output.')
+            [System.Management.Automation.CompletionResult]::new('--no-color', 'no-color', [System.Management.Automation.CompletionResultType]::ParameterName, 'Colorize the output.')
+            [System.Management.Automation.CompletionResult]::new('-f', 'f', [System.Management.Automation.CompletionResultType]::ParameterName, 'Output format.')
+            [System.Management.Automation.CompletionResult]::new('--format', 'format', [System.Management.Automation.CompletionResultType]::ParameterName, 'Output format.')
+            [System.Management.Automation.CompletionResult]::new('-o', 'o', [System.Management.Automation.CompletionResultType]::ParameterName, '-o')
+            [System.Management.Automation.CompletionResult]::new('--output', 'output', [System.Management.Automation.CompletionResultType]::ParameterName, '--output')
+            [System.Management.Automation.CompletionResult]::new('-h', 'h', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--help', 'help', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            break
+        }
+        'ex;remote' {
+            [System.Management.Automation.CompletionResult]::new('-h', 'h', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--help', 'help', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('add', 'add', [System.Management.Automation.CompletionResultType]::ParameterValue, 'Add a remote.')
+            break
+        }
+        'ex;remote;add' {
+            [System.Management.Automation.CompletionResult]::new('-j', 'j', [System.Management.Automation.CompletionResultType]::ParameterName, '-j')
+            [System.Management.Automation.CompletionResult]::new('--jobs', 'jobs', [System.Management.Automation.CompletionResultType]::ParameterName, '--jobs')
+            [System.Management.Automation.CompletionResult]::new('-h', 'h', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--help', 'help', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            break
+        }
+        'ex;completions' {
+            [System.Management.Automation.CompletionResult]::new('-h', 'h', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('--help', 'help', [System.Management.Automation.CompletionResultType]::ParameterName, 'Show this message.')
+            [System.Management.Automation.CompletionResult]::new('bash', 'bash', [System.Management.Automation.CompletionResultType]::ParameterValue, 'bash')
+            [System.Management.Automation.CompletionResult]::new('zsh', 'zsh', [System.Management.Automation.CompletionResultType]::ParameterValue, 'zsh')
+            [System.Management.Automation.CompletionResult]::new('fish', 'fish', [System.Management.Automation.CompletionResultType]::ParameterValue, 'fish')
+            [System.Management.Automation.CompletionResult]::new('powershell', 'powershell', [System.Management.Automation.CompletionResultType]::ParameterValue, 'powershell')
+            break
+        }
+    })
+
+    $completions.Where{ $_.CompletionText -like "$wordToComplete*" } |
+        Sort-Object -Property ListItemText
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use eclip::{man_pages, Application, Command, SubCommand};
+
+use common::assert_golden;
+
+/// Run the first command.
+///
+/// Takes two integers and prints them.
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+struct Command1 {
+    /// Print nothing.
+    #[option(short = "q")]
+    quiet: bool,
+    #[option(long = "dry-run", env = "EX_DRY_RUN")]
+    dry_run: bool,
+    #[argument]
+    a: i32,
+    #[argument]
+    b: Vec<u32>,
+}
+
+impl Command for Command1 {
+    type Output = ();
+
+    fn run(&self) {}
+}
+
+/// Copy a file on Windows.
+///
+/// Reads a path such as C:\temp\in.txt, where - means stdin.
+///
+/// .hidden files are copied too.
+#[derive(Command, Debug)]
+#[allow(dead_code)]
+struct Command3 {
+    /// The file to write, such as \\server\share\out.txt.
+    ///
+    /// '-' writes to stdout.
+    #[option(short = "o", long)]
+    output: Option<String>,
+    #[option(long, default = 4)]
+    jobs: u32,
+    #[argument]
+    input: Option<String>,
+}
+
+impl Command for Command3 {
+    type Output = ();
+
+    fn run(&self) {}
+}
+
+fn application() -> Application<'static> {
+    Application::new("ex")
+        .set_about("An example of eclip.")
+        .set_version("0.1.0")
+        .register::<Command1>("cmd1")
+        .add_subcommand_with_about(
+            "sub",
+            "Commands in a group.",
+            SubCommand::new().register::<Command3>("cmd3"),
+        )
+}
+
+#[test]
+fn renders_a_page_for_every_command() {
+    let pages = application().man_pages();
+    let names: Vec<&str> = pages.iter().map(|page| page.file_name.as_str()).collect();
+    assert_eq!(names, ["ex.1", "ex-cmd1.1", "ex-sub.1", "ex-sub-cmd3.1"]);
+    for page in &pages {
+        assert_golden(&format!("man/{}", page.file_name), &page.content);
+    }
+}
+
+#[test]
+fn escapes_dashes_backslashes_and_leading_dots() {
+    let pages = man_pages(&application().spec(), "0.1.0");
+    let page = pages
+        .iter()
+        .find(|page| page.file_name == "ex-sub-cmd3.1")
+        .unwrap();
+    assert!(page
+        .content
+        .contains("C:\\etemp\\ein.txt, where \\- means stdin."));
+    assert!(page
+        .content
+        .contains("\n\\&.hidden files are copied too.\n"));
+    assert!(page
+        .content
+        .contains("such as \\e\\eserver\\eshare\\eout.txt.\n"));
+    assert!(page.content.contains("\n\\&'\\-' writes to stdout.\n"));
+    assert!(page
+        .content
+        .contains("\\fB\\-o\\fR, \\fB\\-\\-output\\fR \\fIOUTPUT\\fR"));
+}
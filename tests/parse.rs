@@ -0,0 +1,499 @@
+use std::env;
+
+use eclip::{ArgsNew, Command, ErrorKind};
+
+#[derive(Command, Debug, PartialEq)]
+struct Log {
+    #[option(long, conflicts_with = "verbose")]
+    quiet: bool,
+    #[option(long)]
+    verbose: bool,
+}
+
+#[test]
+fn conflicting_options() {
+    let err = Log::new(["--quiet", "--verbose"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    assert_eq!(
+        err.to_string().lines().next(),
+        Some("\"--quiet\" cannot be used with \"--verbose\"")
+    );
+
+    let log = Log::new(["--quiet"]).unwrap();
+    assert_eq!(
+        log,
+        Log {
+            quiet: true,
+            verbose: false
+        }
+    );
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct EnvLog {
+    #[option(long, conflicts_with = "verbose")]
+    quiet: bool,
+    #[option(long, env = "ECLIP_TEST_ENV_LOG_VERBOSE")]
+    verbose: bool,
+}
+
+#[test]
+fn a_value_from_the_environment_conflicts_with_nothing() {
+    env::set_var("ECLIP_TEST_ENV_LOG_VERBOSE", "true");
+    let log = EnvLog::new(["--quiet"]).unwrap();
+    assert_eq!(
+        log,
+        EnvLog {
+            quiet: true,
+            verbose: true
+        }
+    );
+}
+
+#[derive(Command, Debug, PartialEq)]
+#[group(name = "format", members = ["json", "table"], required)]
+struct Format {
+    #[option(long)]
+    json: bool,
+    #[option(long)]
+    table: bool,
+}
+
+#[test]
+fn exclusive_required_group() {
+    let err = Format::new(["--json", "--table"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+
+    let err = Format::new(Vec::<String>::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+    assert_eq!(err.missing(), ["<format: --json|--table>"]);
+
+    let format = Format::new(["--table"]).unwrap();
+    assert_eq!(
+        format,
+        Format {
+            json: false,
+            table: true
+        }
+    );
+}
+
+#[derive(Command, Debug, PartialEq)]
+#[group(name = "output", members = ["json", "table"], multiple = true, required)]
+struct Output {
+    #[option(long)]
+    json: bool,
+    #[option(long)]
+    table: bool,
+}
+
+#[test]
+fn group_of_multiple_members() {
+    let output = Output::new(["--json", "--table"]).unwrap();
+    assert_eq!(
+        output,
+        Output {
+            json: true,
+            table: true
+        }
+    );
+
+    let err = Output::new(Vec::<String>::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+}
+
+#[derive(Command, Debug, PartialEq)]
+#[group(name = "format", members = ["json", "table"], required)]
+struct EnvFormat {
+    #[option(long, env = "ECLIP_TEST_ENV_FORMAT_JSON")]
+    json: bool,
+    #[option(long)]
+    table: bool,
+}
+
+#[test]
+fn a_value_from_the_environment_meets_a_required_group() {
+    env::set_var("ECLIP_TEST_ENV_FORMAT_JSON", "true");
+    let format = EnvFormat::new(Vec::<String>::new()).unwrap();
+    assert_eq!(
+        format,
+        EnvFormat {
+            json: true,
+            table: false
+        }
+    );
+
+    // Only the command line counts for the exclusivity of the group.
+    let format = EnvFormat::new(["--table"]).unwrap();
+    assert_eq!(
+        format,
+        EnvFormat {
+            json: true,
+            table: true
+        }
+    );
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Login {
+    #[option(long, requires = "password")]
+    user: Option<String>,
+    #[option(long)]
+    password: Option<String>,
+}
+
+#[test]
+fn an_option_requires_another() {
+    let err = Login::new(["--user", "me"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingRequirement);
+    assert_eq!(
+        err.to_string().lines().next(),
+        Some("\"--user\" requires \"--password\"")
+    );
+
+    let login = Login::new(["--user", "me", "--password", "secret"]).unwrap();
+    assert_eq!(login.password.as_deref(), Some("secret"));
+
+    let login = Login::new(["--password", "secret"]).unwrap();
+    assert_eq!(login.user, None);
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct EnvLogin {
+    #[option(long, requires = "password", env = "ECLIP_TEST_ENV_LOGIN_USER")]
+    user: Option<String>,
+    #[option(long, env = "ECLIP_TEST_ENV_LOGIN_PASSWORD")]
+    password: Option<String>,
+}
+
+#[test]
+fn a_value_from_the_environment_meets_requires_but_does_not_require() {
+    env::set_var("ECLIP_TEST_ENV_LOGIN_USER", "env-user");
+    let login = EnvLogin::new(Vec::<String>::new()).unwrap();
+    assert_eq!(login.user.as_deref(), Some("env-user"));
+
+    env::set_var("ECLIP_TEST_ENV_LOGIN_PASSWORD", "env-secret");
+    let login = EnvLogin::new(["--user", "me"]).unwrap();
+    assert_eq!(login.user.as_deref(), Some("me"));
+    assert_eq!(login.password.as_deref(), Some("env-secret"));
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Fetch {
+    #[option(long, required_unless = "all")]
+    name: Option<String>,
+    #[option(long)]
+    all: bool,
+}
+
+#[test]
+fn required_unless_another_is_given() {
+    let err = Fetch::new(Vec::<String>::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+    assert_eq!(err.missing(), ["--name"]);
+
+    let fetch = Fetch::new(["--all"]).unwrap();
+    assert_eq!(
+        fetch,
+        Fetch {
+            name: None,
+            all: true
+        }
+    );
+
+    let fetch = Fetch::new(["--name", "origin"]).unwrap();
+    assert_eq!(fetch.name.as_deref(), Some("origin"));
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct EnvFetch {
+    #[option(long, required_unless = "all")]
+    name: Option<String>,
+    #[option(long, env = "ECLIP_TEST_ENV_FETCH_ALL")]
+    all: bool,
+}
+
+#[test]
+fn a_value_from_the_environment_meets_required_unless() {
+    env::set_var("ECLIP_TEST_ENV_FETCH_ALL", "true");
+    let fetch = EnvFetch::new(Vec::<String>::new()).unwrap();
+    assert_eq!(
+        fetch,
+        EnvFetch {
+            name: None,
+            all: true
+        }
+    );
+}
+
+#[cfg(feature = "config")]
+mod config {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use eclip::{Application, Command, ErrorKind};
+
+    /// Writes `content` to a config file of its own for the test `name`.
+    fn config_file(name: &str, content: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("eclip-test-{}-{}.toml", name, std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[derive(Command, Debug, PartialEq)]
+    #[group(name = "format", members = ["json", "table"], required)]
+    struct List {
+        #[option(long, conflicts_with = "quiet")]
+        json: bool,
+        #[option(long)]
+        table: bool,
+        #[option(long)]
+        quiet: bool,
+        #[option(long, requires = "password")]
+        user: Option<String>,
+        #[option(long)]
+        password: Option<String>,
+        #[option(long, required_unless = "all")]
+        name: Option<String>,
+        #[option(long)]
+        all: bool,
+    }
+
+    #[test]
+    fn values_from_a_config_file_are_defaults() {
+        let path = config_file(
+            "relations",
+            "json = true\nquiet = true\npassword = \"secret\"\nall = true\n",
+        );
+        let app = Application::new("app").set_config(&path);
+
+        let list: List = app.try_parse_from(["app", "--user", "me"]).unwrap();
+        assert_eq!(
+            list,
+            List {
+                json: true,
+                table: false,
+                quiet: true,
+                user: Some("me".to_string()),
+                password: Some("secret".to_string()),
+                name: None,
+                all: true,
+            }
+        );
+
+        let list: List = app.try_parse_from(["app", "--table"]).unwrap();
+        assert!(list.json && list.table);
+
+        let err = app
+            .try_parse_from::<List, _, _>(["app", "--json", "--quiet"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn a_nested_array_is_an_error() {
+        let path = config_file("nested", "password = [[1]]\njson = true\nall = true\n");
+        let app = Application::new("app").set_config(&path);
+        let err = app.try_parse_from::<List, _, _>(["app"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidConfig);
+        assert!(err.origin().unwrap().starts_with("key password in "));
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Copy {
+    #[argument(min = 1, max = 2)]
+    files: Vec<String>,
+    #[argument]
+    dest: Option<String>,
+}
+
+#[test]
+fn bounded_variadic_followed_by_an_optional_argument() {
+    let copy = Copy::new(["a"]).unwrap();
+    assert_eq!(
+        copy,
+        Copy {
+            files: vec!["a".to_string()],
+            dest: None
+        }
+    );
+
+    let copy = Copy::new(["a", "b"]).unwrap();
+    assert_eq!(copy.files, ["a", "b"]);
+    assert_eq!(copy.dest, None);
+
+    let copy = Copy::new(["a", "b", "c"]).unwrap();
+    assert_eq!(copy.files, ["a", "b"]);
+    assert_eq!(copy.dest.as_deref(), Some("c"));
+
+    let err = Copy::new(["a", "b", "c", "d"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedArgument);
+    assert_eq!(err.token(), Some("d"));
+
+    let err = Copy::new(Vec::<String>::new()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+    assert_eq!(err.missing(), ["<files>..."]);
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Pair {
+    #[argument(min = 2, max = 2)]
+    pair: Vec<u32>,
+    #[argument]
+    last: String,
+}
+
+#[test]
+fn fixed_variadic_followed_by_a_required_argument() {
+    let pair = Pair::new(["1", "2", "x"]).unwrap();
+    assert_eq!(
+        pair,
+        Pair {
+            pair: vec![1, 2],
+            last: "x".to_string()
+        }
+    );
+
+    let err = Pair::new(["1"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WrongValueCount);
+    assert_eq!(err.field(), Some("pair"));
+
+    let err = Pair::new(["1", "2"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingArgument);
+    assert_eq!(err.missing(), ["<last>"]);
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Files {
+    #[argument]
+    first: i32,
+    #[argument]
+    second: Option<i32>,
+    #[argument]
+    rest: Vec<String>,
+}
+
+#[test]
+fn optional_and_unbounded_arguments() {
+    let files = Files::new(["1"]).unwrap_err();
+    assert_eq!(files.kind(), ErrorKind::MissingArgument);
+    assert_eq!(files.missing(), ["<rest>..."]);
+
+    let files = Files::new(["1", "-2", "a", "b"]).unwrap();
+    assert_eq!(
+        files,
+        Files {
+            first: 1,
+            second: Some(-2),
+            rest: vec!["a".to_string(), "b".to_string()]
+        }
+    );
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Exec {
+    #[option(short = "q")]
+    quiet: bool,
+    #[argument]
+    program: String,
+    #[argument(trailing)]
+    args: Vec<String>,
+}
+
+#[test]
+fn trailing_argument_is_read_verbatim() {
+    let exec = Exec::new(["-q", "grep", "-h", "--", "-q", "x"]).unwrap();
+    assert_eq!(
+        exec,
+        Exec {
+            quiet: true,
+            program: "grep".to_string(),
+            args: ["-h", "--", "-q", "x"].map(String::from).to_vec(),
+        }
+    );
+
+    let exec = Exec::new(["ls"]).unwrap();
+    assert!(exec.args.is_empty());
+
+    let err = Exec::new(["-h", "grep"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::HelpRequested);
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Color {
+    #[option(long, negatable, default = true)]
+    color: bool,
+    #[option(short = "n", long)]
+    name: Option<String>,
+    #[option(short = "j", long)]
+    jobs: u32,
+}
+
+#[test]
+fn negatable_flag() {
+    assert!(Color::new(Vec::<String>::new()).unwrap().color);
+    assert!(!Color::new(["--no-color"]).unwrap().color);
+    assert!(!Color::new(["--color=false"]).unwrap().color);
+
+    let err = Color::new(["--no-color=true"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedValue);
+}
+
+#[test]
+fn last_occurrence_wins() {
+    assert!(Color::new(["--no-color", "--color"]).unwrap().color);
+    assert!(!Color::new(["--color", "--no-color"]).unwrap().color);
+
+    let color = Color::new(["-n", "a", "--name=b", "-j1", "--jobs", "2"]).unwrap();
+    assert_eq!(color.name.as_deref(), Some("b"));
+    assert_eq!(color.jobs, 2);
+}
+
+#[derive(Command, Debug, PartialEq)]
+struct Layout {
+    #[option(short = "t", long, delimiter = ',')]
+    tags: Vec<String>,
+    #[option(long, num_args = 2)]
+    size: Vec<u32>,
+    #[option(long)]
+    at: Option<(i32, i32)>,
+}
+
+#[test]
+fn delimiter_splits_each_value() {
+    let layout = Layout::new(["--tags", "a,b", "-t", "c", "--tags=d,e"]).unwrap();
+    assert_eq!(layout.tags, ["a", "b", "c", "d", "e"]);
+}
+
+#[test]
+fn num_args_reads_several_values_per_occurrence() {
+    let layout = Layout::new(["--size", "80", "24", "--size", "1", "2"]).unwrap();
+    assert_eq!(layout.size, [80, 24, 1, 2]);
+
+    let err = Layout::new(["--size", "80"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingValue);
+    assert_eq!(err.to_string(), "\"--size\" requires 2 arguments");
+
+    let err = Layout::new(["--size", "80", "x"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert_eq!(err.field(), Some("size"));
+}
+
+#[test]
+fn tuple_takes_one_value_per_element() {
+    let layout = Layout::new(["--at", "3", "-4"]).unwrap();
+    assert_eq!(layout.at, Some((3, -4)));
+
+    let layout = Layout::new(["--at=5,6"]).unwrap();
+    assert_eq!(layout.at, Some((5, 6)));
+
+    let err = Layout::new(["--at", "3"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingValue);
+
+    let err = Layout::new(["--at", "3", "x"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+}